@@ -0,0 +1,469 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use near_crypto::{KeyFile, KeyType, Signature};
+use near_workspaces::network::ValidatorKey;
+use near_workspaces::types::SecretKey;
+use near_workspaces::Account;
+use serde::{Deserialize, Serialize};
+
+use access_grants_client::nep413::{self, SignPayload};
+use access_grants_client::RegistryClient;
+use access_grants_core::{
+    consent_hash, delete_grant_message, insert_grant_message, AccessMode, AccessScope, ConsentHash,
+    Ed25519Signature, Nonce, GRANT_MESSAGE_RECIPIENT,
+};
+
+#[derive(Parser)]
+#[command(name = "access-grants", about = "Manage idOS access grants on NEAR")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Insert a grant, either directly or on behalf of an owner who signs it.
+    Insert {
+        #[command(flatten)]
+        network: NetworkArgs,
+        #[command(flatten)]
+        grant: GrantArgs,
+        /// Key file of the grant owner. When given, the grant is inserted by signature.
+        #[arg(long)]
+        signed_by: Option<PathBuf>,
+    },
+    /// Delete a grant, either directly or on behalf of an owner who signs it.
+    Delete {
+        #[command(flatten)]
+        network: NetworkArgs,
+        #[command(flatten)]
+        grant: GrantArgs,
+        /// Key file of the grant owner. When given, the grant is deleted by signature.
+        #[arg(long)]
+        signed_by: Option<PathBuf>,
+    },
+    /// Find grants by owner, grantee and/or data id.
+    Find {
+        #[command(flatten)]
+        network: NetworkArgs,
+        #[arg(long)]
+        owner: Option<String>,
+        #[arg(long)]
+        grantee: Option<String>,
+        #[arg(long)]
+        data_id: Option<String>,
+        #[arg(long)]
+        purpose: Option<String>,
+        /// Only grants allowing this kind of access.
+        #[arg(long)]
+        access_mode: Option<AccessModeArg>,
+    },
+    /// Print the message an owner has to sign for an operation.
+    Message {
+        operation: Operation,
+        #[arg(long)]
+        owner: String,
+        #[command(flatten)]
+        grant: GrantArgs,
+    },
+    /// Sign an operation offline, printing the arguments for the `*_by_signature` call.
+    Sign {
+        operation: Operation,
+        /// Key file of the grant owner.
+        #[arg(long)]
+        key_file: PathBuf,
+        #[command(flatten)]
+        grant: GrantArgs,
+        #[arg(long, default_value = GRANT_MESSAGE_RECIPIENT)]
+        recipient: String,
+    },
+    /// Check the signature in arguments produced by `sign`.
+    Verify {
+        operation: Operation,
+        /// JSON arguments, as printed by `sign`.
+        args: String,
+        #[arg(long, default_value = GRANT_MESSAGE_RECIPIENT)]
+        recipient: String,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Operation {
+    Insert,
+    Delete,
+}
+
+#[derive(Args)]
+struct NetworkArgs {
+    /// RPC address of a running sandbox node.
+    #[arg(long, default_value = "http://localhost:3030")]
+    rpc_url: String,
+    /// Home directory of the sandbox node, where its validator key lives.
+    #[arg(long)]
+    home_dir: PathBuf,
+    #[arg(long)]
+    contract_id: String,
+    /// Key file of the account sending transactions and views.
+    #[arg(long)]
+    key_file: PathBuf,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AccessModeArg {
+    ReadOnly,
+    ReadWrite,
+}
+
+impl From<AccessModeArg> for AccessMode {
+    fn from(mode: AccessModeArg) -> Self {
+        match mode {
+            AccessModeArg::ReadOnly => AccessMode::ReadOnly,
+            AccessModeArg::ReadWrite => AccessMode::ReadWrite,
+        }
+    }
+}
+
+#[derive(Args)]
+struct GrantArgs {
+    #[arg(long)]
+    grantee: String,
+    #[arg(long)]
+    data_id: String,
+    #[arg(long)]
+    locked_until: Option<u64>,
+    /// Why the grantee may use the data. Only for inserts.
+    #[arg(long)]
+    purpose: Option<String>,
+    /// How the grantee may use the data. Only for inserts.
+    #[arg(long)]
+    access_mode: Option<AccessModeArg>,
+    /// Comma-separated fields the access is limited to.
+    #[arg(long, value_delimiter = ',', requires = "access_mode")]
+    fields: Vec<String>,
+    /// File with the terms the owner consented to; the grant keeps their hash. Only for inserts.
+    #[arg(long)]
+    consent_file: Option<PathBuf>,
+    /// When the grantee's access starts, in nanoseconds since the epoch. Only for inserts.
+    #[arg(long)]
+    valid_from: Option<u64>,
+}
+
+impl GrantArgs {
+    fn access(&self) -> Option<AccessScope> {
+        self.access_mode.map(|mode| AccessScope {
+            mode: mode.into(),
+            fields: self.fields.clone(),
+        })
+    }
+
+    fn consent_hash(&self) -> anyhow::Result<Option<ConsentHash>> {
+        self.consent_file
+            .as_ref()
+            .map(|path| {
+                let text = std::fs::read_to_string(path)
+                    .with_context(|| format!("Can't read consent file {:?}", path))?;
+                Ok(consent_hash(&text))
+            })
+            .transpose()
+    }
+
+    // The arguments of the `*_by_signature` call for `owner`, with a fresh nonce and the signature still to fill in.
+    fn into_signed_args(self, owner: String) -> anyhow::Result<SignedArgs> {
+        let access = self.access();
+        let consent_hash = self.consent_hash()?;
+
+        Ok(SignedArgs {
+            owner,
+            grantee: self.grantee,
+            data_id: self.data_id,
+            locked_until: self.locked_until,
+            purpose: self.purpose,
+            access,
+            consent_hash,
+            valid_from: self.valid_from,
+            nonce: nep413::generate_nonce().into(),
+            signature: Ed25519Signature([0; 64]),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignedArgs {
+    owner: String,
+    grantee: String,
+    data_id: String,
+    locked_until: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    purpose: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    access: Option<AccessScope>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    consent_hash: Option<ConsentHash>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    valid_from: Option<u64>,
+    nonce: Nonce,
+    signature: Ed25519Signature,
+}
+
+fn parse_public_key(public_key: &str) -> anyhow::Result<access_grants_core::PublicKey> {
+    public_key
+        .parse()
+        .map_err(|_| anyhow!("Invalid public key: {:?}", public_key))
+}
+
+fn operation_message(operation: Operation, args: &SignedArgs) -> anyhow::Result<String> {
+    let owner = parse_public_key(&args.owner)?;
+    let grantee = parse_public_key(&args.grantee)?;
+
+    Ok(match operation {
+        Operation::Insert => insert_grant_message(
+            &owner,
+            &grantee,
+            &args.data_id,
+            args.locked_until,
+            args.purpose.as_deref(),
+            args.access.as_ref(),
+            args.consent_hash.as_ref(),
+            args.valid_from,
+        ),
+        Operation::Delete => {
+            delete_grant_message(&owner, &grantee, &args.data_id, args.locked_until)
+        }
+    })
+}
+
+fn read_secret_key(key_file: &Path) -> anyhow::Result<SecretKey> {
+    let key_file = KeyFile::from_file(key_file)
+        .with_context(|| format!("Can't read key file {:?}", key_file))?;
+
+    Ok(key_file.secret_key.to_string().parse()?)
+}
+
+fn sign(
+    operation: Operation,
+    secret_key: &SecretKey,
+    grant: GrantArgs,
+    recipient: String,
+) -> anyhow::Result<SignedArgs> {
+    let mut args = grant.into_signed_args(secret_key.public_key().to_string())?;
+
+    args.signature = nep413::Payload {
+        message: operation_message(operation, &args)?,
+        nonce: args.nonce.0,
+        recipient,
+        callback_url: None,
+    }
+    .sign_with(secret_key)?
+    .into();
+
+    Ok(args)
+}
+
+fn verify(operation: Operation, args: &SignedArgs, recipient: String) -> anyhow::Result<()> {
+    let message = operation_message(operation, args)?;
+
+    let signature = Signature::from_parts(KeyType::ED25519, &args.signature.0)?;
+    let owner: near_crypto::PublicKey = args.owner.parse()?;
+
+    let hashed_payload = nep413::Payload {
+        message,
+        nonce: args.nonce.0,
+        recipient,
+        callback_url: None,
+    }
+    .to_hashed();
+
+    if !signature.verify(&hashed_payload, &owner) {
+        bail!("Signature doesn't match");
+    }
+
+    Ok(())
+}
+
+async fn connect(network: &NetworkArgs) -> anyhow::Result<RegistryClient> {
+    let worker = near_workspaces::sandbox()
+        .rpc_addr(&network.rpc_url)
+        .validator_key(ValidatorKey::HomeDir(network.home_dir.clone()))
+        .await?;
+    let account = Account::from_file(&network.key_file, &worker)?;
+
+    Ok(RegistryClient::new(account, network.contract_id.parse()?))
+}
+
+fn print_json(value: &impl Serialize) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    match Cli::parse().command {
+        Command::Insert {
+            network,
+            grant,
+            signed_by,
+        } => {
+            let client = connect(&network).await?;
+            let grantee = grant.grantee.parse()?;
+            let access = grant.access();
+            let consent_hash = grant.consent_hash()?;
+
+            let events = match signed_by {
+                Some(key_file) => {
+                    client
+                        .insert_grant_signed(
+                            &read_secret_key(&key_file)?,
+                            &grantee,
+                            &grant.data_id,
+                            grant.locked_until,
+                            grant.purpose.as_deref(),
+                            access.as_ref(),
+                            consent_hash.as_ref(),
+                            grant.valid_from,
+                        )
+                        .await?
+                }
+                None => {
+                    client
+                        .insert_grant(
+                            &grantee,
+                            &grant.data_id,
+                            grant.locked_until,
+                            grant.purpose.as_deref(),
+                            access.as_ref(),
+                            consent_hash.as_ref(),
+                            grant.valid_from,
+                        )
+                        .await?
+                }
+            };
+
+            events.iter().for_each(|event| println!("{:?}", event));
+        }
+        Command::Delete {
+            network,
+            grant,
+            signed_by,
+        } => {
+            let client = connect(&network).await?;
+            let grantee = grant.grantee.parse()?;
+
+            let events = match signed_by {
+                Some(key_file) => {
+                    client
+                        .delete_grant_signed(
+                            &read_secret_key(&key_file)?,
+                            &grantee,
+                            &grant.data_id,
+                            grant.locked_until,
+                        )
+                        .await?
+                }
+                None => {
+                    client
+                        .delete_grant(&grantee, &grant.data_id, grant.locked_until)
+                        .await?
+                }
+            };
+
+            events.iter().for_each(|event| println!("{:?}", event));
+        }
+        Command::Find {
+            network,
+            owner,
+            grantee,
+            data_id,
+            purpose,
+            access_mode,
+        } => {
+            let client = connect(&network).await?;
+            let owner = owner.map(|owner| owner.parse()).transpose()?;
+            let grantee = grantee.map(|grantee| grantee.parse()).transpose()?;
+
+            print_json(
+                &client
+                    .find_grants(
+                        owner.as_ref(),
+                        grantee.as_ref(),
+                        data_id.as_deref(),
+                        purpose.as_deref(),
+                        access_mode.map(Into::into),
+                    )
+                    .await?,
+            )?;
+        }
+        Command::Message {
+            operation,
+            owner,
+            grant,
+        } => {
+            println!(
+                "{}",
+                operation_message(operation, &grant.into_signed_args(owner)?)?
+            );
+        }
+        Command::Sign {
+            operation,
+            key_file,
+            grant,
+            recipient,
+        } => {
+            print_json(&sign(
+                operation,
+                &read_secret_key(&key_file)?,
+                grant,
+                recipient,
+            )?)?;
+        }
+        Command::Verify {
+            operation,
+            args,
+            recipient,
+        } => {
+            verify(operation, &serde_json::from_str(&args)?, recipient)?;
+            println!("Signature is valid");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[test]
+fn sign_then_verify() {
+    use near_workspaces::types::KeyType;
+
+    let secret_key = SecretKey::from_random(KeyType::ED25519);
+    let grant = GrantArgs {
+        grantee: SecretKey::from_random(KeyType::ED25519)
+            .public_key()
+            .to_string(),
+        data_id: "some data".into(),
+        locked_until: Some(1337),
+        purpose: Some("kyc".into()),
+        access_mode: Some(AccessModeArg::ReadOnly),
+        fields: vec!["name".into()],
+        consent_file: None,
+        valid_from: None,
+    };
+
+    let mut args = sign(
+        Operation::Insert,
+        &secret_key,
+        grant,
+        GRANT_MESSAGE_RECIPIENT.into(),
+    )
+    .unwrap();
+
+    verify(Operation::Insert, &args, GRANT_MESSAGE_RECIPIENT.into()).unwrap();
+    assert!(verify(Operation::Delete, &args, GRANT_MESSAGE_RECIPIENT.into()).is_err());
+
+    args.purpose = Some("marketing".into());
+    assert!(verify(Operation::Insert, &args, GRANT_MESSAGE_RECIPIENT.into()).is_err());
+
+    args.purpose = Some("kyc".into());
+    args.locked_until = Some(0);
+    assert!(verify(Operation::Insert, &args, GRANT_MESSAGE_RECIPIENT.into()).is_err());
+}
@@ -0,0 +1,1477 @@
+use near_workspaces::result::ExecutionSuccess;
+use near_workspaces::types::{PublicKey, SecretKey};
+use near_workspaces::{Account, AccountId};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+
+pub mod nep413;
+
+pub use access_grants_core::events::{
+    self, decode_event, decode_events, AccessRequestApproved, AccessRequestRejected,
+    AccessRequested, AllowListEnabled, AllowListEnabledV0, EncryptionKeyPublished,
+    EncryptionKeyPublishedV0, FractalRegistryEvent, GrantDeleted, GrantEventV0, GrantEventV1,
+    GrantEventV2, GrantEventV3, GrantEventV4, GrantInserted, GrantKeySet, GrantKeySetV0,
+    GrantLockExtended, GrantLockExtendedV0, GrantRenounced, GranteeListUpdated,
+    GranteeListUpdatedV0, GranteeProfileRegistered, GranteeProfileRegisteredV0, GranteeVerified,
+    GranteeVerifiedV0, LockPoliciesSet, LockPoliciesSetV0,
+};
+pub use access_grants_core::merkle::{verify_grant_proof, GrantProof, MerkleLeaf};
+pub use access_grants_core::{
+    consent_hash, derive_grant_id, AccessMode, AccessScope, ChangeKind, ConsentHash, ContentHash,
+    DeletionReason, EncryptionKey, Grant, GrantScope, GranteeList, LockPolicy,
+};
+use access_grants_core::{
+    delete_grant_message, delete_prefix_grant_message, extend_lock_message, insert_grant_message,
+    insert_prefix_grant_message, publish_encryption_key_message, renounce_grant_message,
+    revoke_all_message, set_allow_list_enabled_message, set_grant_key_message,
+    update_grantee_list_message, Base64VecU8, Ed25519Signature, Nonce, RegistryError,
+    GRANT_MESSAGE_RECIPIENT,
+};
+use nep413::SignPayload;
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GrantsForKey {
+    pub grantee: Option<PublicKey>,
+    pub data_id: Option<String>,
+    pub grants: Vec<Grant>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RevokeAllResult {
+    pub revoked: u32,
+    pub timelocked: Vec<Grant>,
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccessRequest {
+    pub id: u64,
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub purpose: String,
+    pub desired_lock: Option<u64>,
+    pub requested_at: u64,
+    pub expires_at: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GrantTombstone {
+    pub grant: Grant,
+    pub deleted_at: u64,
+    pub deleted_by: PublicKey,
+    pub reason: DeletionReason,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GrantChange {
+    pub seq: u64,
+    pub kind: ChangeKind,
+    pub grant: Grant,
+    pub block_height: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChangesPage {
+    pub oldest_seq: u64,
+    pub latest_seq: u64,
+    pub changes: Vec<GrantChange>,
+}
+
+impl ChangesPage {
+    // Whether changes after `seq` were already overwritten in the contract's change log.
+    pub fn has_gap_after(&self, seq: u64) -> bool {
+        seq.saturating_add(1) < self.oldest_seq
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GranteeEncryptionKey {
+    pub encryption_key: EncryptionKey,
+    pub rotation: u32,
+    pub published_at: u64,
+    pub nonce: Nonce,
+    pub signature: Ed25519Signature,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GranteeProfile {
+    pub name: String,
+    pub url: String,
+    pub logo_hash: Option<ContentHash>,
+    pub privacy_policy_hash: Option<ContentHash>,
+    pub verified: bool,
+    pub updated_at: u64,
+}
+
+// Keys as the message builders (and grants) have them, rather than the workspaces type.
+fn core_public_key(public_key: &PublicKey) -> anyhow::Result<access_grants_core::PublicKey> {
+    Ok(public_key.to_string().parse()?)
+}
+
+// Messages are built here rather than fetched from the contract, so an RPC node can't get anything else signed.
+fn sign(secret_key: &SecretKey, message: String) -> anyhow::Result<(Nonce, Ed25519Signature)> {
+    let nonce = nep413::generate_nonce();
+    let signature = nep413::Payload {
+        message,
+        nonce,
+        recipient: GRANT_MESSAGE_RECIPIENT.into(),
+        callback_url: None,
+    }
+    .sign_with(secret_key)?;
+
+    Ok((nonce.into(), signature.into()))
+}
+
+// Contract failures come back as `RegistryError`, so callers can `downcast_ref` them instead of matching strings.
+fn registry_error(error: impl Into<anyhow::Error>) -> anyhow::Error {
+    let error = error.into();
+
+    match RegistryError::find_in(&error.to_string()) {
+        Some(registry_error) => registry_error.into(),
+        None => error,
+    }
+}
+
+// A `FractalRegistry` contract, as seen by `account`. Views and calls both go through that account.
+pub struct RegistryClient {
+    account: Account,
+    contract_id: AccountId,
+}
+
+impl RegistryClient {
+    pub fn new(account: Account, contract_id: AccountId) -> Self {
+        Self {
+            account,
+            contract_id,
+        }
+    }
+
+    pub fn account(&self) -> &Account {
+        &self.account
+    }
+
+    pub fn contract_id(&self) -> &AccountId {
+        &self.contract_id
+    }
+
+    async fn view<T: DeserializeOwned>(
+        &self,
+        function: &str,
+        args: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        let result = self
+            .account
+            .view(&self.contract_id, function)
+            .args_json(args)
+            .await
+            .map_err(registry_error)?;
+
+        Ok(result.json::<T>()?)
+    }
+
+    async fn call(
+        &self,
+        function: &str,
+        args: serde_json::Value,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let result = self.transact(function, args).await?;
+
+        Ok(decode_events(result.logs())?)
+    }
+
+    // For the few calls whose return value matters more than the events they emit.
+    async fn call_for_result<T: DeserializeOwned>(
+        &self,
+        function: &str,
+        args: serde_json::Value,
+    ) -> anyhow::Result<T> {
+        Ok(self.transact(function, args).await?.json::<T>()?)
+    }
+
+    async fn transact(
+        &self,
+        function: &str,
+        args: serde_json::Value,
+    ) -> anyhow::Result<ExecutionSuccess> {
+        self.account
+            .call(&self.contract_id, function)
+            .args_json(args)
+            .transact()
+            .await?
+            .into_result()
+            .map_err(registry_error)
+    }
+
+    pub async fn grant_message_recipient(&self) -> anyhow::Result<String> {
+        self.view("grant_message_recipient", json!({})).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_grant(
+        &self,
+        grantee: &PublicKey,
+        data_id: &str,
+        locked_until: Option<u64>,
+        purpose: Option<&str>,
+        access: Option<&AccessScope>,
+        consent_hash: Option<&ConsentHash>,
+        valid_from: Option<u64>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "insert_grant",
+            json!({
+                "grantee": grantee,
+                "data_id": data_id,
+                "locked_until": locked_until,
+                "purpose": purpose,
+                "access": access,
+                "consent_hash": consent_hash,
+                "valid_from": valid_from,
+            }),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_grant_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id: &str,
+        locked_until: Option<u64>,
+        purpose: Option<&str>,
+        access: Option<&AccessScope>,
+        consent_hash: Option<&ConsentHash>,
+        valid_from: Option<u64>,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "insert_grant_by_signature_message",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": data_id,
+                "locked_until": locked_until,
+                "purpose": purpose,
+                "access": access,
+                "consent_hash": consent_hash,
+                "valid_from": valid_from,
+            }),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_grant_by_signature(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id: &str,
+        locked_until: Option<u64>,
+        purpose: Option<&str>,
+        access: Option<&AccessScope>,
+        consent_hash: Option<&ConsentHash>,
+        valid_from: Option<u64>,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "insert_grant_by_signature",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": data_id,
+                "locked_until": locked_until,
+                "purpose": purpose,
+                "access": access,
+                "consent_hash": consent_hash,
+                "valid_from": valid_from,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Builds and signs the insertGrant message with `owner_sk`, then submits it through this client's account.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_grant_signed(
+        &self,
+        owner_sk: &SecretKey,
+        grantee: &PublicKey,
+        data_id: &str,
+        locked_until: Option<u64>,
+        purpose: Option<&str>,
+        access: Option<&AccessScope>,
+        consent_hash: Option<&ConsentHash>,
+        valid_from: Option<u64>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let owner = owner_sk.public_key();
+        let message = insert_grant_message(
+            &core_public_key(&owner)?,
+            &core_public_key(grantee)?,
+            data_id,
+            locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
+        );
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        self.insert_grant_by_signature(
+            &owner,
+            grantee,
+            data_id,
+            locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
+            &nonce,
+            &signature,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_prefix_grant(
+        &self,
+        grantee: &PublicKey,
+        data_id_prefix: &str,
+        locked_until: Option<u64>,
+        purpose: Option<&str>,
+        access: Option<&AccessScope>,
+        consent_hash: Option<&ConsentHash>,
+        valid_from: Option<u64>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "insert_prefix_grant",
+            json!({
+                "grantee": grantee,
+                "data_id_prefix": data_id_prefix,
+                "locked_until": locked_until,
+                "purpose": purpose,
+                "access": access,
+                "consent_hash": consent_hash,
+                "valid_from": valid_from,
+            }),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_prefix_grant_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id_prefix: &str,
+        locked_until: Option<u64>,
+        purpose: Option<&str>,
+        access: Option<&AccessScope>,
+        consent_hash: Option<&ConsentHash>,
+        valid_from: Option<u64>,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "insert_prefix_grant_by_signature_message",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id_prefix": data_id_prefix,
+                "locked_until": locked_until,
+                "purpose": purpose,
+                "access": access,
+                "consent_hash": consent_hash,
+                "valid_from": valid_from,
+            }),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_prefix_grant_by_signature(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id_prefix: &str,
+        locked_until: Option<u64>,
+        purpose: Option<&str>,
+        access: Option<&AccessScope>,
+        consent_hash: Option<&ConsentHash>,
+        valid_from: Option<u64>,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "insert_prefix_grant_by_signature",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id_prefix": data_id_prefix,
+                "locked_until": locked_until,
+                "purpose": purpose,
+                "access": access,
+                "consent_hash": consent_hash,
+                "valid_from": valid_from,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Builds and signs the prefix insertGrant message with `owner_sk`, then submits it through this client's account.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert_prefix_grant_signed(
+        &self,
+        owner_sk: &SecretKey,
+        grantee: &PublicKey,
+        data_id_prefix: &str,
+        locked_until: Option<u64>,
+        purpose: Option<&str>,
+        access: Option<&AccessScope>,
+        consent_hash: Option<&ConsentHash>,
+        valid_from: Option<u64>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let owner = owner_sk.public_key();
+        let message = insert_prefix_grant_message(
+            &core_public_key(&owner)?,
+            &core_public_key(grantee)?,
+            data_id_prefix,
+            locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
+        );
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        self.insert_prefix_grant_by_signature(
+            &owner,
+            grantee,
+            data_id_prefix,
+            locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
+            &nonce,
+            &signature,
+        )
+        .await
+    }
+
+    pub async fn delete_grant(
+        &self,
+        grantee: &PublicKey,
+        data_id: &str,
+        locked_until: Option<u64>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "delete_grant",
+            json!({
+                "grantee": grantee,
+                "data_id": data_id,
+                "locked_until": locked_until,
+            }),
+        )
+        .await
+    }
+
+    pub async fn delete_grant_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id: &str,
+        locked_until: Option<u64>,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "delete_grant_by_signature_message",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": data_id,
+                "locked_until": locked_until,
+            }),
+        )
+        .await
+    }
+
+    pub async fn delete_grant_by_signature(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id: &str,
+        locked_until: Option<u64>,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "delete_grant_by_signature",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": data_id,
+                "locked_until": locked_until,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Builds and signs the deleteGrant message with `owner_sk`, then submits it through this client's account.
+    pub async fn delete_grant_signed(
+        &self,
+        owner_sk: &SecretKey,
+        grantee: &PublicKey,
+        data_id: &str,
+        locked_until: Option<u64>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let owner = owner_sk.public_key();
+        let message = delete_grant_message(
+            &core_public_key(&owner)?,
+            &core_public_key(grantee)?,
+            data_id,
+            locked_until,
+        );
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        self.delete_grant_by_signature(&owner, grantee, data_id, locked_until, &nonce, &signature)
+            .await
+    }
+
+    pub async fn delete_prefix_grant(
+        &self,
+        grantee: &PublicKey,
+        data_id_prefix: &str,
+        locked_until: Option<u64>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "delete_prefix_grant",
+            json!({
+                "grantee": grantee,
+                "data_id_prefix": data_id_prefix,
+                "locked_until": locked_until,
+            }),
+        )
+        .await
+    }
+
+    pub async fn delete_prefix_grant_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id_prefix: &str,
+        locked_until: Option<u64>,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "delete_prefix_grant_by_signature_message",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id_prefix": data_id_prefix,
+                "locked_until": locked_until,
+            }),
+        )
+        .await
+    }
+
+    pub async fn delete_prefix_grant_by_signature(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id_prefix: &str,
+        locked_until: Option<u64>,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "delete_prefix_grant_by_signature",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id_prefix": data_id_prefix,
+                "locked_until": locked_until,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Builds and signs the prefix deleteGrant message with `owner_sk`, then submits it through this client's account.
+    pub async fn delete_prefix_grant_signed(
+        &self,
+        owner_sk: &SecretKey,
+        grantee: &PublicKey,
+        data_id_prefix: &str,
+        locked_until: Option<u64>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let owner = owner_sk.public_key();
+        let message = delete_prefix_grant_message(
+            &core_public_key(&owner)?,
+            &core_public_key(grantee)?,
+            data_id_prefix,
+            locked_until,
+        );
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        self.delete_prefix_grant_by_signature(
+            &owner,
+            grantee,
+            data_id_prefix,
+            locked_until,
+            &nonce,
+            &signature,
+        )
+        .await
+    }
+
+    pub async fn renounce_grant(
+        &self,
+        owner: &PublicKey,
+        data_id: &str,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "renounce_grant",
+            json!({"owner": owner, "data_id": data_id}),
+        )
+        .await
+    }
+
+    pub async fn renounce_grant_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id: &str,
+        revision: u32,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "renounce_grant_by_signature_message",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": data_id,
+                "revision": revision,
+            }),
+        )
+        .await
+    }
+
+    pub async fn renounce_grant_by_signature(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id: &str,
+        revision: u32,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "renounce_grant_by_signature",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": data_id,
+                "revision": revision,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Builds and signs the renounceGrant message with `grantee_sk`, then submits it through this client's account.
+    pub async fn renounce_grant_signed(
+        &self,
+        grantee_sk: &SecretKey,
+        owner: &PublicKey,
+        data_id: &str,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let grantee = grantee_sk.public_key();
+        let revision = self.renounce_revision(owner, &grantee, data_id).await? + 1;
+        let message = renounce_grant_message(
+            &core_public_key(owner)?,
+            &core_public_key(&grantee)?,
+            data_id,
+            revision,
+        );
+        let (nonce, signature) = sign(grantee_sk, message)?;
+
+        self.renounce_grant_by_signature(owner, &grantee, data_id, revision, &nonce, &signature)
+            .await
+    }
+
+    pub async fn renounce_revision(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+        data_id: &str,
+    ) -> anyhow::Result<u32> {
+        self.view(
+            "renounce_revision",
+            json!({"owner": owner, "grantee": grantee, "data_id": data_id}),
+        )
+        .await
+    }
+
+    pub async fn extend_lock(
+        &self,
+        grant_id: &str,
+        new_locked_until: u64,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "extend_lock",
+            json!({"grant_id": grant_id, "new_locked_until": new_locked_until}),
+        )
+        .await
+    }
+
+    pub async fn extend_lock_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        grant_id: &str,
+        new_locked_until: u64,
+        revision: u32,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "extend_lock_by_signature_message",
+            json!({
+                "owner": owner,
+                "grant_id": grant_id,
+                "new_locked_until": new_locked_until,
+                "revision": revision,
+            }),
+        )
+        .await
+    }
+
+    pub async fn extend_lock_by_signature(
+        &self,
+        owner: &PublicKey,
+        grant_id: &str,
+        new_locked_until: u64,
+        revision: u32,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "extend_lock_by_signature",
+            json!({
+                "owner": owner,
+                "grant_id": grant_id,
+                "new_locked_until": new_locked_until,
+                "revision": revision,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Builds and signs the extendLock message with `owner_sk`, then submits it through this client's account.
+    pub async fn extend_lock_signed(
+        &self,
+        owner_sk: &SecretKey,
+        grant_id: &str,
+        new_locked_until: u64,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let owner = owner_sk.public_key();
+        let revision = self.grant_lock_revision(grant_id).await? + 1;
+        let message = extend_lock_message(
+            &core_public_key(&owner)?,
+            grant_id,
+            new_locked_until,
+            revision,
+        );
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        self.extend_lock_by_signature(
+            &owner,
+            grant_id,
+            new_locked_until,
+            revision,
+            &nonce,
+            &signature,
+        )
+        .await
+    }
+
+    pub async fn grant_lock_revision(&self, grant_id: &str) -> anyhow::Result<u32> {
+        self.view("grant_lock_revision", json!({ "grant_id": grant_id }))
+            .await
+    }
+
+    pub async fn set_grant_key(
+        &self,
+        grant_id: &str,
+        encrypted_key: &[u8],
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "set_grant_key",
+            json!({
+                "grant_id": grant_id,
+                "encrypted_key": Base64VecU8(encrypted_key.to_vec()),
+            }),
+        )
+        .await
+    }
+
+    pub async fn set_grant_key_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        grant_id: &str,
+        encrypted_key: &[u8],
+        revision: u32,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "set_grant_key_by_signature_message",
+            json!({
+                "owner": owner,
+                "grant_id": grant_id,
+                "encrypted_key": Base64VecU8(encrypted_key.to_vec()),
+                "revision": revision,
+            }),
+        )
+        .await
+    }
+
+    pub async fn set_grant_key_by_signature(
+        &self,
+        owner: &PublicKey,
+        grant_id: &str,
+        encrypted_key: &[u8],
+        revision: u32,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "set_grant_key_by_signature",
+            json!({
+                "owner": owner,
+                "grant_id": grant_id,
+                "encrypted_key": Base64VecU8(encrypted_key.to_vec()),
+                "revision": revision,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Builds and signs the setGrantKey message with `owner_sk`, then submits it through this client's account.
+    pub async fn set_grant_key_signed(
+        &self,
+        owner_sk: &SecretKey,
+        grant_id: &str,
+        encrypted_key: &[u8],
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let owner = owner_sk.public_key();
+        let revision = self.grant_key_revision(grant_id).await? + 1;
+        let message =
+            set_grant_key_message(&core_public_key(&owner)?, grant_id, encrypted_key, revision);
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        self.set_grant_key_by_signature(
+            &owner,
+            grant_id,
+            encrypted_key,
+            revision,
+            &nonce,
+            &signature,
+        )
+        .await
+    }
+
+    // The encrypted key as the owner left it; decrypting it is up to the grantee.
+    pub async fn get_grant_key(&self, grant_id: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let encrypted_key: Option<Base64VecU8> = self
+            .view("get_grant_key", json!({ "grant_id": grant_id }))
+            .await?;
+
+        Ok(encrypted_key.map(|encrypted_key| encrypted_key.0))
+    }
+
+    pub async fn grant_key_revision(&self, grant_id: &str) -> anyhow::Result<u32> {
+        self.view("grant_key_revision", json!({ "grant_id": grant_id }))
+            .await
+    }
+
+    pub async fn publish_encryption_key_by_signature_message(
+        &self,
+        grantee: &PublicKey,
+        encryption_key: &EncryptionKey,
+        rotation: u32,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "publish_encryption_key_by_signature_message",
+            json!({
+                "grantee": grantee,
+                "encryption_key": encryption_key,
+                "rotation": rotation,
+            }),
+        )
+        .await
+    }
+
+    pub async fn publish_encryption_key_by_signature(
+        &self,
+        grantee: &PublicKey,
+        encryption_key: &EncryptionKey,
+        rotation: u32,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "publish_encryption_key_by_signature",
+            json!({
+                "grantee": grantee,
+                "encryption_key": encryption_key,
+                "rotation": rotation,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Publishes `encryption_key` as the grantee's next rotation, signed with `grantee_sk`.
+    pub async fn publish_encryption_key_signed(
+        &self,
+        grantee_sk: &SecretKey,
+        encryption_key: &EncryptionKey,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let grantee = grantee_sk.public_key();
+        let rotation = self
+            .grantee_encryption_key(&grantee)
+            .await?
+            .map_or(0, |key| key.rotation)
+            + 1;
+        let message =
+            publish_encryption_key_message(&core_public_key(&grantee)?, encryption_key, rotation);
+        let (nonce, signature) = sign(grantee_sk, message)?;
+
+        self.publish_encryption_key_by_signature(
+            &grantee,
+            encryption_key,
+            rotation,
+            &nonce,
+            &signature,
+        )
+        .await
+    }
+
+    pub async fn grantee_encryption_key(
+        &self,
+        grantee: &PublicKey,
+    ) -> anyhow::Result<Option<GranteeEncryptionKey>> {
+        self.view("grantee_encryption_key", json!({ "grantee": grantee }))
+            .await
+    }
+
+    pub async fn grantee_encryption_key_history(
+        &self,
+        grantee: &PublicKey,
+    ) -> anyhow::Result<Vec<GranteeEncryptionKey>> {
+        self.view(
+            "grantee_encryption_key_history",
+            json!({ "grantee": grantee }),
+        )
+        .await
+    }
+
+    // Registers this client's account key as a grantee, under this profile.
+    pub async fn register_grantee_profile(
+        &self,
+        name: &str,
+        url: &str,
+        logo_hash: Option<&ContentHash>,
+        privacy_policy_hash: Option<&ContentHash>,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "register_grantee_profile",
+            json!({
+                "name": name,
+                "url": url,
+                "logo_hash": logo_hash,
+                "privacy_policy_hash": privacy_policy_hash,
+            }),
+        )
+        .await
+    }
+
+    pub async fn grantee_profile(
+        &self,
+        grantee: &PublicKey,
+    ) -> anyhow::Result<Option<GranteeProfile>> {
+        self.view("grantee_profile", json!({ "grantee": grantee }))
+            .await
+    }
+
+    // Only works from the contract account, and only on registered grantees.
+    pub async fn set_grantee_verified(
+        &self,
+        grantee: &PublicKey,
+        verified: bool,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "set_grantee_verified",
+            json!({"grantee": grantee, "verified": verified}),
+        )
+        .await
+    }
+
+    pub async fn require_registered_grantees(&self) -> anyhow::Result<bool> {
+        self.view("require_registered_grantees", json!({})).await
+    }
+
+    // Only works from the contract account.
+    pub async fn set_require_registered_grantees(
+        &self,
+        required: bool,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "set_require_registered_grantees",
+            json!({ "required": required }),
+        )
+        .await
+    }
+
+    pub async fn update_grantee_list(
+        &self,
+        list: GranteeList,
+        grantee: &PublicKey,
+        listed: bool,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "update_grantee_list",
+            json!({"list": list, "grantee": grantee, "listed": listed}),
+        )
+        .await
+    }
+
+    pub async fn update_grantee_list_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        list: GranteeList,
+        grantee: &PublicKey,
+        listed: bool,
+        revision: u32,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "update_grantee_list_by_signature_message",
+            json!({
+                "owner": owner,
+                "list": list,
+                "grantee": grantee,
+                "listed": listed,
+                "revision": revision,
+            }),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_grantee_list_by_signature(
+        &self,
+        owner: &PublicKey,
+        list: GranteeList,
+        grantee: &PublicKey,
+        listed: bool,
+        revision: u32,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "update_grantee_list_by_signature",
+            json!({
+                "owner": owner,
+                "list": list,
+                "grantee": grantee,
+                "listed": listed,
+                "revision": revision,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Signs the owner's next list revision with `owner_sk`, then submits it through this client's account.
+    pub async fn update_grantee_list_signed(
+        &self,
+        owner_sk: &SecretKey,
+        list: GranteeList,
+        grantee: &PublicKey,
+        listed: bool,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let owner = owner_sk.public_key();
+        let revision = self.grantee_list_revision(&owner).await? + 1;
+        let message = update_grantee_list_message(
+            &core_public_key(&owner)?,
+            list,
+            &core_public_key(grantee)?,
+            listed,
+            revision,
+        );
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        self.update_grantee_list_by_signature(
+            &owner, list, grantee, listed, revision, &nonce, &signature,
+        )
+        .await
+    }
+
+    pub async fn set_allow_list_enabled(
+        &self,
+        enabled: bool,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call("set_allow_list_enabled", json!({ "enabled": enabled }))
+            .await
+    }
+
+    pub async fn set_allow_list_enabled_by_signature(
+        &self,
+        owner: &PublicKey,
+        enabled: bool,
+        revision: u32,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call(
+            "set_allow_list_enabled_by_signature",
+            json!({
+                "owner": owner,
+                "enabled": enabled,
+                "revision": revision,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Signs the owner's next list revision with `owner_sk`, then submits it through this client's account.
+    pub async fn set_allow_list_enabled_signed(
+        &self,
+        owner_sk: &SecretKey,
+        enabled: bool,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        let owner = owner_sk.public_key();
+        let revision = self.grantee_list_revision(&owner).await? + 1;
+        let message = set_allow_list_enabled_message(&core_public_key(&owner)?, enabled, revision);
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        self.set_allow_list_enabled_by_signature(&owner, enabled, revision, &nonce, &signature)
+            .await
+    }
+
+    pub async fn allow_list_enabled(&self, owner: &PublicKey) -> anyhow::Result<bool> {
+        self.view("allow_list_enabled", json!({ "owner": owner }))
+            .await
+    }
+
+    pub async fn grantee_list(
+        &self,
+        owner: &PublicKey,
+        list: GranteeList,
+    ) -> anyhow::Result<Vec<PublicKey>> {
+        self.view("grantee_list", json!({"owner": owner, "list": list}))
+            .await
+    }
+
+    pub async fn grantee_list_revision(&self, owner: &PublicKey) -> anyhow::Result<u32> {
+        self.view("grantee_list_revision", json!({ "owner": owner }))
+            .await
+    }
+
+    // Replaces the lock policies of this client's account key, as a grantee.
+    pub async fn set_lock_policies(
+        &self,
+        policies: &[LockPolicy],
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call("set_lock_policies", json!({ "policies": policies }))
+            .await
+    }
+
+    pub async fn lock_policies(&self, grantee: &PublicKey) -> anyhow::Result<Vec<LockPolicy>> {
+        self.view("lock_policies", json!({ "grantee": grantee }))
+            .await
+    }
+
+    pub async fn min_locked_until(
+        &self,
+        grantee: &PublicKey,
+        data_id: &str,
+        scope: Option<GrantScope>,
+    ) -> anyhow::Result<u64> {
+        self.view(
+            "min_locked_until",
+            json!({"grantee": grantee, "data_id": data_id, "scope": scope}),
+        )
+        .await
+    }
+
+    pub async fn revoke_all(
+        &self,
+        grantee: Option<&PublicKey>,
+        limit: Option<u32>,
+        from: Option<&str>,
+    ) -> anyhow::Result<RevokeAllResult> {
+        self.call_for_result(
+            "revoke_all",
+            json!({"grantee": grantee, "limit": limit, "from": from}),
+        )
+        .await
+    }
+
+    pub async fn revoke_all_by_signature_message(
+        &self,
+        owner: &PublicKey,
+        grantee: Option<&PublicKey>,
+        revision: u32,
+    ) -> anyhow::Result<String> {
+        self.view(
+            "revoke_all_by_signature_message",
+            json!({"owner": owner, "grantee": grantee, "revision": revision}),
+        )
+        .await
+    }
+
+    pub async fn revoke_all_by_signature(
+        &self,
+        owner: &PublicKey,
+        grantee: Option<&PublicKey>,
+        revision: u32,
+        limit: Option<u32>,
+        nonce: &Nonce,
+        signature: &Ed25519Signature,
+    ) -> anyhow::Result<RevokeAllResult> {
+        self.call_for_result(
+            "revoke_all_by_signature",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "revision": revision,
+                "limit": limit,
+                "nonce": nonce,
+                "signature": signature,
+            }),
+        )
+        .await
+    }
+
+    // Signs the revokeAll message once with `owner_sk`, then keeps submitting it until nothing revocable is left.
+    // Returns the last call's result, which lists the grants still held back by timelocks.
+    pub async fn revoke_all_signed(
+        &self,
+        owner_sk: &SecretKey,
+        grantee: Option<&PublicKey>,
+    ) -> anyhow::Result<RevokeAllResult> {
+        let owner = owner_sk.public_key();
+        let revision = self.revoke_all_revision(&owner).await? + 1;
+        let message = revoke_all_message(
+            &core_public_key(&owner)?,
+            grantee.map(core_public_key).transpose()?.as_ref(),
+            revision,
+        );
+        let (nonce, signature) = sign(owner_sk, message)?;
+
+        loop {
+            let result = self
+                .revoke_all_by_signature(&owner, grantee, revision, None, &nonce, &signature)
+                .await?;
+
+            if result.next.is_none() {
+                return Ok(result);
+            }
+        }
+    }
+
+    pub async fn revoke_all_revision(&self, owner: &PublicKey) -> anyhow::Result<u32> {
+        self.view("revoke_all_revision", json!({ "owner": owner }))
+            .await
+    }
+
+    pub async fn grants_for(
+        &self,
+        grantee: &PublicKey,
+        data_id: &str,
+    ) -> anyhow::Result<Vec<Grant>> {
+        self.view(
+            "grants_for",
+            json!({"grantee": grantee, "data_id": data_id}),
+        )
+        .await
+    }
+
+    pub async fn has_access(&self, grantee: &PublicKey, data_id: &str) -> anyhow::Result<bool> {
+        self.view(
+            "has_access",
+            json!({"grantee": grantee, "data_id": data_id}),
+        )
+        .await
+    }
+
+    pub async fn verify_consent(&self, grant_id: &str, text: &str) -> anyhow::Result<bool> {
+        self.view(
+            "verify_consent",
+            json!({"grant_id": grant_id, "text": text}),
+        )
+        .await
+    }
+
+    pub async fn find_grants(
+        &self,
+        owner: Option<&PublicKey>,
+        grantee: Option<&PublicKey>,
+        data_id: Option<&str>,
+        purpose: Option<&str>,
+        access_mode: Option<AccessMode>,
+    ) -> anyhow::Result<Vec<Grant>> {
+        self.view(
+            "find_grants",
+            json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": data_id,
+                "purpose": purpose,
+                "access_mode": access_mode,
+            }),
+        )
+        .await
+    }
+
+    pub async fn find_grants_multi(
+        &self,
+        owner: Option<&PublicKey>,
+        grantees: &[PublicKey],
+        data_ids: &[&str],
+        active_only: bool,
+    ) -> anyhow::Result<Vec<GrantsForKey>> {
+        self.view(
+            "find_grants_multi",
+            json!({
+                "owner": owner,
+                "grantees": grantees,
+                "data_ids": data_ids,
+                "active_only": active_only,
+            }),
+        )
+        .await
+    }
+
+    // Filed as this client's account key. Returns the request id.
+    pub async fn request_access(
+        &self,
+        owner: &PublicKey,
+        data_id: &str,
+        purpose: &str,
+        desired_lock: Option<u64>,
+    ) -> anyhow::Result<u64> {
+        self.call_for_result(
+            "request_access",
+            json!({
+                "owner": owner,
+                "data_id": data_id,
+                "purpose": purpose,
+                "desired_lock": desired_lock,
+            }),
+        )
+        .await
+    }
+
+    pub async fn access_request(&self, id: u64) -> anyhow::Result<Option<AccessRequest>> {
+        self.view("access_request", json!({ "id": id })).await
+    }
+
+    pub async fn pending_access_requests(
+        &self,
+        owner: &PublicKey,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> anyhow::Result<Vec<AccessRequest>> {
+        self.view(
+            "pending_access_requests",
+            json!({"owner": owner, "from": from, "limit": limit}),
+        )
+        .await
+    }
+
+    pub async fn approve_access_request(
+        &self,
+        id: u64,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call("approve_access_request", json!({ "id": id }))
+            .await
+    }
+
+    pub async fn reject_access_request(
+        &self,
+        id: u64,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call("reject_access_request", json!({ "id": id }))
+            .await
+    }
+
+    // In nanoseconds, like block timestamps.
+    pub async fn access_request_ttl(&self) -> anyhow::Result<u64> {
+        self.view("access_request_ttl", json!({})).await
+    }
+
+    // Only works from the contract account.
+    pub async fn set_access_request_ttl(
+        &self,
+        ttl: u64,
+    ) -> anyhow::Result<Vec<FractalRegistryEvent>> {
+        self.call("set_access_request_ttl", json!({ "ttl": ttl }))
+            .await
+    }
+
+    pub async fn grant_history(
+        &self,
+        owner: &PublicKey,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> anyhow::Result<Vec<GrantTombstone>> {
+        self.view(
+            "grant_history",
+            json!({"owner": owner, "from": from, "limit": limit}),
+        )
+        .await
+    }
+
+    pub async fn revocations_for(
+        &self,
+        grantee: &PublicKey,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> anyhow::Result<Vec<GrantTombstone>> {
+        self.view(
+            "revocations_for",
+            json!({"grantee": grantee, "from": from, "limit": limit}),
+        )
+        .await
+    }
+
+    pub async fn latest_change_seq(&self) -> anyhow::Result<u64> {
+        self.view("latest_change_seq", json!({})).await
+    }
+
+    pub async fn changes_since(&self, seq: u64, limit: Option<u64>) -> anyhow::Result<ChangesPage> {
+        self.view("changes_since", json!({"seq": seq, "limit": limit}))
+            .await
+    }
+
+    pub async fn grants_root(&self) -> anyhow::Result<String> {
+        self.view("grants_root", json!({})).await
+    }
+
+    pub async fn grant_proof(&self, grant_id: &str) -> anyhow::Result<GrantProof> {
+        self.view("grant_proof", json!({ "grant_id": grant_id }))
+            .await
+    }
+}
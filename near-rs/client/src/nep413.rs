@@ -0,0 +1,40 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use rand::random;
+
+use near_workspaces::types::SecretKey;
+
+pub use access_grants_core::{nep413_hashed_payload, Nep413Payload as Payload, NEP413_TAG};
+
+pub trait SignPayload {
+    fn to_hashed(&self) -> [u8; 32];
+    fn sign_with(&self, secret_key: &SecretKey) -> anyhow::Result<[u8; 64]>;
+}
+
+impl SignPayload for Payload {
+    fn to_hashed(&self) -> [u8; 32] {
+        nep413_hashed_payload(self)
+    }
+
+    fn sign_with(&self, secret_key: &SecretKey) -> anyhow::Result<[u8; 64]> {
+        raw_sign(secret_key, &self.to_hashed())
+    }
+}
+
+pub fn generate_nonce() -> [u8; 32] {
+    let mut result = [0; 32];
+    for x in result.iter_mut() {
+        *x = random()
+    }
+    result
+}
+
+fn raw_sign(secret_key: &SecretKey, data: &[u8]) -> anyhow::Result<[u8; 64]> {
+    let crypto_secret_key = near_crypto::SecretKey::from_str(secret_key.to_string().as_str())?;
+
+    match crypto_secret_key.sign(data) {
+        near_crypto::Signature::ED25519(signature) => Ok(signature.to_bytes()),
+        _ => bail!("Only ED25519 keys can sign NEP-413 messages"),
+    }
+}
@@ -0,0 +1,409 @@
+use std::{env, fs};
+
+use lazy_static::lazy_static;
+use near_workspaces::types::{KeyType, PublicKey, SecretKey};
+
+use access_grants_client::nep413::SignPayload;
+use access_grants_client::{
+    consent_hash, derive_grant_id, verify_grant_proof, AccessMode, AccessScope, DeletionReason,
+    FractalRegistryEvent, Grant, GrantDeleted, GrantEventV1, GrantEventV2, GrantEventV3,
+    GrantInserted, GrantScope, RegistryClient,
+};
+use access_grants_core::RegistryError;
+
+lazy_static! {
+    static ref WASM: Vec<u8> = {
+        let wasm_arg: String = env::var("CONTRACT_LOCATION").unwrap_or(
+            "../contract/target/wasm32-unknown-unknown/release/access_grants.wasm".into(),
+        );
+        let wasm_filepath = fs::canonicalize(env::current_dir().unwrap().join(wasm_arg)).unwrap();
+        std::fs::read(wasm_filepath).unwrap()
+    };
+}
+
+async fn scenario_base() -> anyhow::Result<RegistryClient> {
+    let worker = near_workspaces::sandbox().await?;
+    let contract = worker.dev_deploy(&WASM).await?;
+    let account = worker.dev_create_account().await?;
+
+    Ok(RegistryClient::new(account, contract.id().clone()))
+}
+
+// Like `scenario_base`, plus a client for the contract account itself, which the admin calls need.
+async fn scenario_with_admin() -> anyhow::Result<(RegistryClient, RegistryClient)> {
+    let worker = near_workspaces::sandbox().await?;
+    let contract = worker.dev_deploy(&WASM).await?;
+    let account = worker.dev_create_account().await?;
+
+    Ok((
+        RegistryClient::new(account, contract.id().clone()),
+        RegistryClient::new(contract.as_account().clone(), contract.id().clone()),
+    ))
+}
+
+// Grants come back with the contract's key type, not the workspaces one.
+fn core_key(public_key: &PublicKey) -> access_grants_core::PublicKey {
+    public_key.to_string().parse().unwrap()
+}
+
+#[tokio::test]
+async fn insert_and_delete() -> anyhow::Result<()> {
+    let client = scenario_base().await?;
+    let owner = client.account().secret_key().public_key();
+    let grantee = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    let events = client
+        .insert_grant(&grantee, "A1", None, None, None, None, None)
+        .await?;
+    let event_data = GrantEventV1 {
+        owner: owner.to_string().parse().unwrap(),
+        grantee: grantee.to_string().parse().unwrap(),
+        data_id: "A1".into(),
+        scope: GrantScope::Exact,
+        locked_until: 0,
+    };
+    assert_eq!(
+        events,
+        vec![FractalRegistryEvent::GrantInserted(GrantInserted::V4(
+            GrantEventV3::from(GrantEventV2::from(event_data.clone())).into()
+        ))]
+    );
+
+    assert_eq!(
+        client.grants_for(&grantee, "A1").await?,
+        vec![Grant {
+            owner: core_key(&owner),
+            grantee: core_key(&grantee),
+            data_id: "A1".into(),
+            scope: GrantScope::Exact,
+            locked_until: 0,
+            purpose: None,
+            access: None,
+            consent_hash: None,
+            valid_from: 0,
+        }]
+    );
+
+    let err = client
+        .insert_grant(&grantee, "A1", None, None, None, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<RegistryError>(),
+        Some(&RegistryError::GrantAlreadyExists)
+    );
+
+    let events = client.delete_grant(&grantee, "A1", None).await?;
+    assert_eq!(
+        events,
+        vec![FractalRegistryEvent::GrantDeleted(GrantDeleted::V1(
+            event_data
+        ))]
+    );
+
+    assert_eq!(
+        client
+            .find_grants(Some(&owner), None, None, None, None)
+            .await?,
+        vec![]
+    );
+    assert_eq!(client.grant_history(&owner, None, None).await?.len(), 1);
+    assert_eq!(client.revocations_for(&grantee, None, None).await?.len(), 1);
+
+    let page = client.changes_since(0, None).await?;
+    assert_eq!(page.latest_seq, 2);
+    assert!(!page.has_gap_after(0));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn signed_insert_and_delete() -> anyhow::Result<()> {
+    let client = scenario_base().await?;
+    let owner_sk = SecretKey::from_random(KeyType::ED25519);
+    let owner = owner_sk.public_key();
+    let grantee = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    let access = AccessScope {
+        mode: AccessMode::ReadOnly,
+        fields: vec!["name".into()],
+    };
+
+    client
+        .insert_grant_signed(
+            &owner_sk,
+            &grantee,
+            "A1",
+            None,
+            Some("kyc"),
+            Some(&access),
+            Some(&consent_hash("I agree to share my name.")),
+            None,
+        )
+        .await?;
+    assert_eq!(
+        client
+            .find_grants(
+                Some(&owner),
+                Some(&grantee),
+                None,
+                Some("kyc"),
+                Some(AccessMode::ReadOnly)
+            )
+            .await?,
+        vec![Grant {
+            owner: core_key(&owner),
+            grantee: core_key(&grantee),
+            data_id: "A1".into(),
+            scope: GrantScope::Exact,
+            locked_until: 0,
+            purpose: Some("kyc".into()),
+            access: Some(access),
+            consent_hash: Some(consent_hash("I agree to share my name.")),
+            valid_from: 0,
+        }]
+    );
+    assert_eq!(
+        client
+            .find_grants(Some(&owner), None, None, None, Some(AccessMode::ReadWrite))
+            .await?,
+        vec![]
+    );
+
+    let other_sk = SecretKey::from_random(KeyType::ED25519);
+    let message = client
+        .delete_grant_by_signature_message(&owner, &grantee, "A1", None)
+        .await?;
+    let nonce = access_grants_client::nep413::generate_nonce();
+    let wrong_signature = access_grants_client::nep413::Payload {
+        message,
+        nonce,
+        recipient: client.grant_message_recipient().await?,
+        callback_url: None,
+    }
+    .sign_with(&other_sk)?;
+    let err = client
+        .delete_grant_by_signature(
+            &owner,
+            &grantee,
+            "A1",
+            None,
+            &nonce.into(),
+            &wrong_signature.into(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<RegistryError>(),
+        Some(&RegistryError::InvalidSignature)
+    );
+
+    client
+        .delete_grant_signed(&owner_sk, &grantee, "A1", None)
+        .await?;
+    assert_eq!(
+        client
+            .find_grants(Some(&owner), None, None, None, None)
+            .await?,
+        vec![]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn prefix_grants() -> anyhow::Result<()> {
+    let client = scenario_base().await?;
+    let grantee = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    client
+        .insert_prefix_grant(&grantee, "creds/", None, None, None, None, None)
+        .await?;
+
+    assert!(client.has_access(&grantee, "creds/passport").await?);
+    assert!(!client.has_access(&grantee, "wallets/1").await?);
+    assert_eq!(
+        client.grants_for(&grantee, "creds/passport").await?[0].scope,
+        GrantScope::Prefix
+    );
+
+    client.delete_prefix_grant(&grantee, "creds/", None).await?;
+    assert!(!client.has_access(&grantee, "creds/passport").await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn signed_prefix_grants() -> anyhow::Result<()> {
+    let client = scenario_base().await?;
+    let owner_sk = SecretKey::from_random(KeyType::ED25519);
+    let owner = owner_sk.public_key();
+    let grantee = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    let message = client
+        .insert_prefix_grant_by_signature_message(
+            &owner, &grantee, "creds/", None, None, None, None, None,
+        )
+        .await?;
+    assert!(message.contains("dataIdPrefix: creds/"));
+
+    client
+        .insert_prefix_grant_signed(&owner_sk, &grantee, "creds/", None, None, None, None, None)
+        .await?;
+    let grants = client.grants_for(&grantee, "creds/passport").await?;
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].owner, core_key(&owner));
+    assert_eq!(grants[0].scope, GrantScope::Prefix);
+
+    client
+        .delete_prefix_grant_signed(&owner_sk, &grantee, "creds/", None)
+        .await?;
+    assert!(!client.has_access(&grantee, "creds/passport").await?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn access_request_approval() -> anyhow::Result<()> {
+    let owner_client = scenario_base().await?;
+    let owner = owner_client.account().secret_key().public_key();
+
+    let grantee_account = owner_client
+        .account()
+        .create_subaccount("grantee")
+        .transact()
+        .await?
+        .into_result()?;
+    let grantee = grantee_account.secret_key().public_key();
+    let grantee_client = RegistryClient::new(grantee_account, owner_client.contract_id().clone());
+
+    let id = grantee_client
+        .request_access(&owner, "A1", "KYC check", None)
+        .await?;
+
+    let pending = owner_client
+        .pending_access_requests(&owner, None, None)
+        .await?;
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].grantee, grantee);
+    assert_eq!(pending[0].purpose, "KYC check");
+
+    assert_eq!(
+        grantee_client
+            .approve_access_request(id)
+            .await
+            .unwrap_err()
+            .downcast_ref::<RegistryError>(),
+        Some(&RegistryError::NotOwner)
+    );
+
+    owner_client.approve_access_request(id).await?;
+    assert!(owner_client.has_access(&grantee, "A1").await?);
+    assert_eq!(
+        owner_client
+            .pending_access_requests(&owner, None, None)
+            .await?,
+        vec![]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn access_request_ttl() -> anyhow::Result<()> {
+    let (client, admin) = scenario_with_admin().await?;
+    let owner = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    // A week.
+    assert_eq!(client.access_request_ttl().await?, 604_800_000_000_000);
+
+    assert_eq!(
+        client
+            .set_access_request_ttl(60_000_000_000)
+            .await
+            .unwrap_err()
+            .downcast_ref::<RegistryError>(),
+        Some(&RegistryError::NotContractAccount)
+    );
+
+    admin.set_access_request_ttl(60_000_000_000).await?;
+    assert_eq!(client.access_request_ttl().await?, 60_000_000_000);
+
+    let id = client
+        .request_access(&owner, "A1", "KYC check", None)
+        .await?;
+    let request = client.access_request(id).await?.unwrap();
+    assert_eq!(request.expires_at - request.requested_at, 60_000_000_000);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn grantee_verification() -> anyhow::Result<()> {
+    let (client, admin) = scenario_with_admin().await?;
+    let grantee = client.account().secret_key().public_key();
+    let unregistered = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    client
+        .register_grantee_profile("Acme", "https://acme.example", None, None)
+        .await?;
+
+    assert_eq!(
+        client
+            .set_grantee_verified(&grantee, true)
+            .await
+            .unwrap_err()
+            .downcast_ref::<RegistryError>(),
+        Some(&RegistryError::NotContractAccount)
+    );
+
+    admin.set_grantee_verified(&grantee, true).await?;
+    assert!(client.grantee_profile(&grantee).await?.unwrap().verified);
+
+    admin.set_require_registered_grantees(true).await?;
+    assert!(client.require_registered_grantees().await?);
+
+    client
+        .insert_grant(&grantee, "A1", None, None, None, None, None)
+        .await?;
+    assert_eq!(
+        client
+            .insert_grant(&unregistered, "A1", None, None, None, None, None)
+            .await
+            .unwrap_err()
+            .downcast_ref::<RegistryError>(),
+        Some(&RegistryError::GranteeNotRegistered)
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn grant_proofs_verify_locally() -> anyhow::Result<()> {
+    let client = scenario_base().await?;
+    let grantee = SecretKey::from_random(KeyType::ED25519).public_key();
+
+    client
+        .insert_grant(&grantee, "A1", None, None, None, None, None)
+        .await?;
+    client
+        .insert_grant(&grantee, "A2", None, None, None, None, None)
+        .await?;
+
+    let grant = client.grants_for(&grantee, "A1").await?.remove(0);
+    let grant_id = derive_grant_id(&grant);
+    let root = client.grants_root().await?;
+    let proof = client.grant_proof(&grant_id).await?;
+    assert!(verify_grant_proof(&root, &grant_id, Some(&grant), &proof));
+    assert!(!verify_grant_proof(&root, &grant_id, None, &proof));
+
+    client.delete_grant(&grantee, "A1", None).await?;
+    let root = client.grants_root().await?;
+    let proof = client.grant_proof(&grant_id).await?;
+    assert!(verify_grant_proof(&root, &grant_id, None, &proof));
+
+    let tombstones = client.revocations_for(&grantee, None, None).await?;
+    assert_eq!(tombstones[0].reason, DeletionReason::Deleted);
+
+    Ok(())
+}
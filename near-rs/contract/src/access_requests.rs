@@ -0,0 +1,409 @@
+// Grantees asking for access before owners grant it. A request sits in its owner's pending list until the owner
+// approves it (which inserts the grant), rejects it, or it expires.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, Duration, EpochHeight, NearSchema, PublicKey, Timestamp};
+
+use crate::{
+    validate_purpose, FractalRegistry, FractalRegistryEvents, FractalRegistryExt, GrantScope,
+    RegistryError,
+};
+
+pub(crate) const DEFAULT_ACCESS_REQUEST_TTL: Duration = 7 * 24 * 60 * 60 * 1_000_000_000;
+
+// Anyone can file requests against any owner. Capping each grantee's pending requests to an owner keeps one grantee
+// from crowding out the others, and the cap on the owner's whole list bounds it (and the cleanup of it) against
+// grantee keys made up by the dozen.
+const MAX_PENDING_ACCESS_REQUESTS: usize = 10;
+const MAX_OWNER_PENDING_ACCESS_REQUESTS: usize = 100;
+const DEFAULT_ACCESS_REQUESTS_LIMIT: u32 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccessRequest {
+    pub id: u64,
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub purpose: String,
+    pub desired_lock: Option<EpochHeight>,
+    pub requested_at: Timestamp,
+    pub expires_at: Timestamp,
+}
+
+impl AccessRequest {
+    fn is_expired(&self) -> bool {
+        env::block_timestamp() >= self.expires_at
+    }
+}
+
+impl FractalRegistry {
+    fn pending_access_request_ids(&self, owner: &PublicKey) -> Vec<u64> {
+        self.access_request_ids_by_owner
+            .get(owner)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn remove_access_request(&mut self, request: &AccessRequest) {
+        self.access_requests.remove(&request.id);
+
+        for ids in IntoIterator::into_iter([
+            self.access_request_ids_by_owner.get_mut(&request.owner),
+            self.access_request_ids_by_grantee.get_mut(&request.grantee),
+        ])
+        .flatten()
+        {
+            ids.retain(|id| *id != request.id);
+        }
+    }
+
+    // Removes and returns request `id`, as long as the signer owns it and it hasn't expired.
+    fn take_owned_access_request(&mut self, id: u64) -> Result<AccessRequest, RegistryError> {
+        let request = self
+            .access_requests
+            .get(&id)
+            .cloned()
+            .ok_or(RegistryError::AccessRequestNotFound)?;
+
+        if request.owner != env::signer_account_pk() {
+            return Err(RegistryError::NotOwner);
+        }
+
+        if request.is_expired() {
+            return Err(RegistryError::AccessRequestExpired);
+        }
+
+        self.remove_access_request(&request);
+
+        Ok(request)
+    }
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    // Filed by the grantee (the signer). Returns the request id.
+    #[handle_result]
+    pub fn request_access(
+        &mut self,
+        owner: PublicKey,
+        data_id: String,
+        purpose: String,
+        desired_lock: Option<EpochHeight>,
+    ) -> Result<u64, RegistryError> {
+        // It becomes the grant's purpose on approval.
+        validate_purpose(&purpose)?;
+
+        // No point filing a request the owner couldn't approve.
+        let grantee = env::signer_account_pk();
+        self.check_grantee_registered(&grantee)?;
+        self.check_grantee_listed(&owner, &grantee)?;
+
+        // This is also where the grantee's expired requests get cleaned up.
+        let mut pending = 0;
+        let grantee_request_ids = self
+            .access_request_ids_by_grantee
+            .get(&grantee)
+            .cloned()
+            .unwrap_or_default();
+
+        for id in grantee_request_ids {
+            let request = self.access_requests.get(&id).unwrap().clone();
+            if request.is_expired() {
+                self.remove_access_request(&request);
+            } else if request.owner == owner {
+                pending += 1;
+            }
+        }
+
+        if pending >= MAX_PENDING_ACCESS_REQUESTS {
+            return Err(RegistryError::TooManyAccessRequests);
+        }
+
+        // And the owner's, from any grantee.
+        for id in self.pending_access_request_ids(&owner) {
+            let request = self.access_requests.get(&id).unwrap().clone();
+            if request.is_expired() {
+                self.remove_access_request(&request);
+            }
+        }
+
+        if self.pending_access_request_ids(&owner).len() >= MAX_OWNER_PENDING_ACCESS_REQUESTS {
+            return Err(RegistryError::OwnerAccessRequestsFull);
+        }
+
+        self.last_access_request_id += 1;
+        let requested_at = env::block_timestamp();
+
+        let request = AccessRequest {
+            id: self.last_access_request_id,
+            owner: owner.clone(),
+            grantee,
+            data_id,
+            purpose,
+            desired_lock,
+            requested_at,
+            expires_at: requested_at.saturating_add(self.access_request_ttl),
+        };
+
+        self.access_request_ids_by_owner
+            .entry(owner)
+            .or_default()
+            .push(request.id);
+        self.access_request_ids_by_grantee
+            .entry(request.grantee.clone())
+            .or_default()
+            .push(request.id);
+        self.access_requests.insert(request.id, request.clone());
+
+        FractalRegistryEvents::AccessRequested {
+            id: request.id,
+            owner: request.owner,
+            grantee: request.grantee,
+            data_id: request.data_id,
+            purpose: request.purpose,
+            desired_lock: request.desired_lock,
+            expires_at: request.expires_at,
+        }
+        .emit();
+
+        Ok(request.id)
+    }
+
+    pub fn access_request(&self, id: u64) -> Option<AccessRequest> {
+        self.access_requests.get(&id).cloned()
+    }
+
+    // `from` and `limit` count unexpired requests only.
+    pub fn pending_access_requests(
+        &self,
+        owner: PublicKey,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<AccessRequest> {
+        self.pending_access_request_ids(&owner)
+            .iter()
+            .map(|id| self.access_requests.get(id).unwrap().clone())
+            .filter(|request| !request.is_expired())
+            .skip(from.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(DEFAULT_ACCESS_REQUESTS_LIMIT) as usize)
+            .collect()
+    }
+
+    // Inserts the requested grant, owned by the signer.
+    #[handle_result]
+    pub fn approve_access_request(&mut self, id: u64) -> Result<(), RegistryError> {
+        let request = self.take_owned_access_request(id)?;
+
+        self._insert_grant(
+            request.owner.clone(),
+            request.grantee.clone(),
+            request.data_id.clone(),
+            GrantScope::Exact,
+            request.desired_lock,
+            Some(request.purpose.clone()),
+            None,
+            None,
+            None,
+        )?;
+
+        FractalRegistryEvents::AccessRequestApproved {
+            id: request.id,
+            owner: request.owner,
+            grantee: request.grantee,
+            data_id: request.data_id,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    #[handle_result]
+    pub fn reject_access_request(&mut self, id: u64) -> Result<(), RegistryError> {
+        let request = self.take_owned_access_request(id)?;
+
+        FractalRegistryEvents::AccessRequestRejected {
+            id: request.id,
+            owner: request.owner,
+            grantee: request.grantee,
+            data_id: request.data_id,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    pub fn access_request_ttl(&self) -> Duration {
+        self.access_request_ttl
+    }
+
+    // Only applies to requests filed afterwards.
+    #[handle_result]
+    pub fn set_access_request_ttl(&mut self, ttl: Duration) -> Result<(), RegistryError> {
+        self.require_contract_account()?;
+
+        self.access_request_ttl = ttl;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::{testing_env, CurveType};
+
+    use super::*;
+
+    fn owner() -> PublicKey {
+        "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+            .parse()
+            .unwrap()
+    }
+
+    fn grantee() -> PublicKey {
+        "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+            .parse()
+            .unwrap()
+    }
+
+    fn signed_by(signer: PublicKey, block_timestamp: Timestamp) {
+        testing_env!(VMContextBuilder::new()
+            .signer_account_pk(signer)
+            .block_timestamp(block_timestamp)
+            .build());
+    }
+
+    fn request(registry: &mut FractalRegistry, data_id: &str) -> u64 {
+        registry
+            .request_access(owner(), data_id.into(), "KYC".into(), Some(100))
+            .unwrap()
+    }
+
+    #[test]
+    fn approve_inserts_the_grant() {
+        let mut registry = FractalRegistry::default();
+
+        signed_by(grantee(), 1);
+        let approved = request(&mut registry, "A1");
+        let rejected = request(&mut registry, "A2");
+        assert_eq!(
+            registry.pending_access_requests(owner(), None, None).len(),
+            2
+        );
+
+        assert_eq!(
+            registry.approve_access_request(approved),
+            Err(RegistryError::NotOwner)
+        );
+
+        signed_by(owner(), 2);
+        registry.approve_access_request(approved).unwrap();
+        registry.reject_access_request(rejected).unwrap();
+
+        assert_eq!(
+            registry.pending_access_requests(owner(), None, None),
+            vec![]
+        );
+        assert_eq!(
+            registry.approve_access_request(approved),
+            Err(RegistryError::AccessRequestNotFound)
+        );
+
+        let grants = registry
+            .find_grants(Some(owner()), None, None, None, None)
+            .unwrap();
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].grantee, grantee());
+        assert_eq!(grants[0].data_id, "A1");
+        assert_eq!(grants[0].locked_until, 100);
+        assert_eq!(grants[0].purpose.as_deref(), Some("KYC"));
+    }
+
+    #[test]
+    fn requests_expire() {
+        let mut registry = FractalRegistry::default();
+
+        signed_by(grantee(), 1);
+        let id = request(&mut registry, "A1");
+
+        signed_by(owner(), 1 + DEFAULT_ACCESS_REQUEST_TTL);
+        assert_eq!(
+            registry.pending_access_requests(owner(), None, None),
+            vec![]
+        );
+        assert_eq!(
+            registry.approve_access_request(id),
+            Err(RegistryError::AccessRequestExpired)
+        );
+
+        // Filing a new request drops the expired one for good.
+        signed_by(grantee(), 1 + DEFAULT_ACCESS_REQUEST_TTL);
+        request(&mut registry, "A2");
+        assert_eq!(registry.access_request(id), None);
+    }
+
+    #[test]
+    fn pending_requests_are_capped_per_grantee() {
+        let mut registry = FractalRegistry::default();
+
+        signed_by(grantee(), 1);
+        for _ in 0..MAX_PENDING_ACCESS_REQUESTS {
+            request(&mut registry, "A1");
+        }
+        assert_eq!(
+            registry.request_access(owner(), "A1".into(), "KYC".into(), None),
+            Err(RegistryError::TooManyAccessRequests)
+        );
+
+        // The owner still hears from everyone else.
+        let other_grantee: PublicKey = "ed25519:2Mu2J7XP7HUXuhyVFg3RHbyrtJTdUrkUqanNjEDENqhz"
+            .parse()
+            .unwrap();
+        signed_by(other_grantee, 1);
+        request(&mut registry, "A1");
+        assert_eq!(
+            registry.pending_access_requests(owner(), None, None).len(),
+            MAX_PENDING_ACCESS_REQUESTS + 1
+        );
+    }
+
+    #[test]
+    fn pending_requests_are_capped_per_owner() {
+        let mut registry = FractalRegistry::default();
+        let grantee =
+            |i: usize| PublicKey::from_parts(CurveType::ED25519, vec![i as u8; 32]).unwrap();
+
+        for i in 0..MAX_OWNER_PENDING_ACCESS_REQUESTS / MAX_PENDING_ACCESS_REQUESTS {
+            signed_by(grantee(i), 1);
+            for _ in 0..MAX_PENDING_ACCESS_REQUESTS {
+                request(&mut registry, "A1");
+            }
+        }
+
+        signed_by(grantee(100), 2);
+        assert_eq!(
+            registry.request_access(owner(), "A1".into(), "KYC".into(), None),
+            Err(RegistryError::OwnerAccessRequestsFull)
+        );
+        assert_eq!(
+            registry
+                .pending_access_requests(owner(), Some(95), Some(10))
+                .len(),
+            5
+        );
+
+        // Once they expire, the owner's list gets cleaned up on the next request.
+        signed_by(grantee(100), 1 + DEFAULT_ACCESS_REQUEST_TTL);
+        request(&mut registry, "A1");
+        assert_eq!(
+            registry
+                .access_request_ids_by_owner
+                .get(&owner())
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,118 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, BlockHeight, NearSchema, Timestamp};
+
+use crate::{ChangeKind, FractalRegistry, FractalRegistryExt, Grant};
+
+// How many changes we keep around. Older ones get overwritten, so mirrors that fall further behind
+// than this need a full resync.
+const CHANGE_LOG_CAPACITY: u64 = 1024;
+const DEFAULT_CHANGES_LIMIT: u64 = 100;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, NearSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantChange {
+    pub(crate) seq: u64,
+    pub(crate) kind: ChangeKind,
+    pub(crate) grant: Grant,
+    pub(crate) block_height: BlockHeight,
+    pub(crate) timestamp: Timestamp,
+}
+
+// `oldest_seq` is the first change still in the log. If it's past the `seq` a client asked for plus one,
+// the client missed some changes and has to resync from scratch.
+#[derive(Serialize, NearSchema)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChangesPage {
+    pub oldest_seq: u64,
+    pub latest_seq: u64,
+    pub changes: Vec<GrantChange>,
+}
+
+impl FractalRegistry {
+    pub(crate) fn record_change(&mut self, kind: ChangeKind, grant: &Grant) {
+        self.last_change_seq += 1;
+
+        let change = GrantChange {
+            seq: self.last_change_seq,
+            kind,
+            grant: grant.clone(),
+            block_height: env::block_height(),
+            timestamp: env::block_timestamp(),
+        };
+
+        self.recent_changes
+            .insert(change.seq % CHANGE_LOG_CAPACITY, change);
+    }
+
+    fn oldest_change_seq(&self) -> u64 {
+        self.last_change_seq.saturating_sub(CHANGE_LOG_CAPACITY) + 1
+    }
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    pub fn latest_change_seq(&self) -> u64 {
+        self.last_change_seq
+    }
+
+    pub fn changes_since(&self, seq: u64, limit: Option<u64>) -> ChangesPage {
+        let oldest_seq = self.oldest_change_seq();
+        let from = seq.saturating_add(1).max(oldest_seq);
+        let to = from
+            .saturating_add(limit.unwrap_or(DEFAULT_CHANGES_LIMIT))
+            .min(self.last_change_seq.saturating_add(1));
+
+        ChangesPage {
+            oldest_seq,
+            latest_seq: self.last_change_seq,
+            changes: (from..to)
+                .map(|seq| {
+                    self.recent_changes
+                        .get(&(seq % CHANGE_LOG_CAPACITY))
+                        .unwrap()
+                        .clone()
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn changes_since_reports_overwritten_changes() {
+    let mut registry = FractalRegistry::default();
+    let grant = Grant {
+        owner: "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+            .parse()
+            .unwrap(),
+        grantee: "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+            .parse()
+            .unwrap(),
+        data_id: "some data".into(),
+        scope: crate::GrantScope::Exact,
+        locked_until: 0,
+        purpose: None,
+        access: None,
+        consent_hash: None,
+        valid_from: 0,
+    };
+
+    for _ in 0..CHANGE_LOG_CAPACITY + 10 {
+        registry.record_change(ChangeKind::Inserted, &grant);
+    }
+
+    let page = registry.changes_since(0, Some(3));
+    assert_eq!(page.oldest_seq, 11);
+    assert_eq!(page.latest_seq, CHANGE_LOG_CAPACITY + 10);
+    assert_eq!(
+        page.changes.iter().map(|c| c.seq).collect::<Vec<_>>(),
+        vec![11, 12, 13]
+    );
+
+    let page = registry.changes_since(CHANGE_LOG_CAPACITY + 8, None);
+    assert_eq!(
+        page.changes.iter().map(|c| c.seq).collect::<Vec<_>>(),
+        vec![CHANGE_LOG_CAPACITY + 9, CHANGE_LOG_CAPACITY + 10]
+    );
+}
@@ -0,0 +1,127 @@
+// Grantees' X25519 keys, for owners to encrypt data (or grant keys) to. Each one comes with the grantee's signature
+// over it, kept so anyone can check the binding without taking the contract's word for it.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, NearSchema, PublicKey, Timestamp};
+
+use crate::{
+    nep413_verify, publish_encryption_key_message, Ed25519Signature, EncryptionKey,
+    FractalRegistry, FractalRegistryEvents, FractalRegistryExt, Nonce, RegistryError,
+};
+
+// Older keys beyond this many are forgotten. `rotation` keeps counting regardless.
+const MAX_ENCRYPTION_KEY_HISTORY: usize = 20;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GranteeEncryptionKey {
+    pub encryption_key: EncryptionKey,
+    pub rotation: u32,
+    pub published_at: Timestamp,
+    pub nonce: Nonce,
+    pub signature: Ed25519Signature,
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    pub fn publish_encryption_key_by_signature_message(
+        &self,
+        grantee: PublicKey,
+        encryption_key: EncryptionKey,
+        rotation: u32,
+    ) -> String {
+        publish_encryption_key_message(&grantee, &encryption_key, rotation)
+    }
+
+    // Anyone can relay it, since the signature has to be the grantee's. `rotation` has to be one more than the
+    // grantee's latest (or 1 for their first key).
+    #[handle_result]
+    pub fn publish_encryption_key_by_signature(
+        &mut self,
+        grantee: PublicKey,
+        encryption_key: EncryptionKey,
+        rotation: u32,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
+        let latest_rotation = self
+            .grantee_encryption_key(grantee.clone())
+            .map_or(0, |key| key.rotation);
+
+        if latest_rotation.checked_add(1) != Some(rotation) {
+            return Err(RegistryError::InvalidRotation);
+        }
+
+        nep413_verify(
+            publish_encryption_key_message(&grantee, &encryption_key, rotation),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &grantee,
+        )?;
+
+        let history = self
+            .encryption_keys_by_grantee
+            .entry(grantee.clone())
+            .or_default();
+
+        history.push(GranteeEncryptionKey {
+            encryption_key,
+            rotation,
+            published_at: env::block_timestamp(),
+            nonce,
+            signature,
+        });
+
+        if history.len() > MAX_ENCRYPTION_KEY_HISTORY {
+            history.remove(0);
+        }
+
+        FractalRegistryEvents::EncryptionKeyPublished {
+            grantee,
+            encryption_key,
+            rotation,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    // The grantee's current key.
+    pub fn grantee_encryption_key(&self, grantee: PublicKey) -> Option<GranteeEncryptionKey> {
+        self.encryption_keys_by_grantee
+            .get(&grantee)
+            .and_then(|history| history.last().cloned())
+    }
+
+    // Oldest first, so the last one is the current key.
+    pub fn grantee_encryption_key_history(&self, grantee: PublicKey) -> Vec<GranteeEncryptionKey> {
+        self.encryption_keys_by_grantee
+            .get(&grantee)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn rotations_have_to_follow_the_latest() {
+    let mut registry = FractalRegistry::default();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let mut publish = |rotation| {
+        registry.publish_encryption_key_by_signature(
+            grantee.clone(),
+            EncryptionKey([1; 32]),
+            rotation,
+            Nonce([0; 32]),
+            Ed25519Signature([0; 64]),
+        )
+    };
+
+    assert_eq!(publish(0), Err(RegistryError::InvalidRotation));
+    assert_eq!(publish(2), Err(RegistryError::InvalidRotation));
+    assert_eq!(publish(1), Err(RegistryError::InvalidSignature));
+}
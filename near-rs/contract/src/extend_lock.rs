@@ -0,0 +1,185 @@
+use near_sdk::{env, near_bindgen, EpochHeight, PublicKey};
+
+use crate::{
+    extend_lock_message, nep413_verify, ChangeKind, Ed25519Signature, FractalRegistry,
+    FractalRegistryEvents, FractalRegistryExt, Nonce, RegistryError,
+};
+
+#[near_bindgen]
+impl FractalRegistry {
+    // Locks only get longer, and the grant keeps its id (and key, and consent hash). Works on locked grants too:
+    // that's the point.
+    #[handle_result]
+    pub fn extend_lock(
+        &mut self,
+        grant_id: String,
+        new_locked_until: EpochHeight,
+    ) -> Result<(), RegistryError> {
+        let revision = self.grant_lock_revision(grant_id.clone()) + 1;
+
+        self._extend_lock(
+            env::signer_account_pk(),
+            grant_id,
+            new_locked_until,
+            revision,
+        )
+    }
+
+    pub fn extend_lock_by_signature_message(
+        &self,
+        owner: PublicKey,
+        grant_id: String,
+        new_locked_until: EpochHeight,
+        revision: u32,
+    ) -> String {
+        extend_lock_message(&owner, &grant_id, new_locked_until, revision)
+    }
+
+    // `revision` has to be one more than `grant_lock_revision(grant_id)`.
+    #[handle_result]
+    pub fn extend_lock_by_signature(
+        &mut self,
+        owner: PublicKey,
+        grant_id: String,
+        new_locked_until: EpochHeight,
+        revision: u32,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
+        if self.grant_lock_revision(grant_id.clone()).checked_add(1) != Some(revision) {
+            return Err(RegistryError::InvalidLockRevision);
+        }
+
+        nep413_verify(
+            extend_lock_message(&owner, &grant_id, new_locked_until, revision),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &owner,
+        )?;
+
+        self._extend_lock(owner, grant_id, new_locked_until, revision)
+    }
+
+    // How many times the grant's lock was extended. Both direct and signed extensions count, and the count outlives
+    // the grant, for when it's given again.
+    pub fn grant_lock_revision(&self, grant_id: String) -> u32 {
+        self.grant_lock_revisions
+            .get(&grant_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn _extend_lock(
+        &mut self,
+        owner: PublicKey,
+        grant_id: String,
+        new_locked_until: EpochHeight,
+        revision: u32,
+    ) -> Result<(), RegistryError> {
+        let grant = self
+            .grants_by_id
+            .get_mut(&grant_id)
+            .ok_or(RegistryError::GrantNotFound)?;
+
+        if grant.owner != owner {
+            return Err(RegistryError::NotOwner);
+        }
+
+        if new_locked_until <= grant.locked_until {
+            return Err(RegistryError::LockNotExtended);
+        }
+
+        let previous_locked_until = grant.locked_until;
+        grant.locked_until = new_locked_until;
+        let grant = grant.clone();
+
+        self.grant_lock_revisions.insert(grant_id.clone(), revision);
+        self.record_change(ChangeKind::LockExtended, &grant);
+        self.merkle_insert_grant(&grant_id, &grant);
+
+        FractalRegistryEvents::GrantLockExtended {
+            grant_id,
+            owner,
+            grantee: grant.grantee,
+            data_id: grant.data_id,
+            scope: grant.scope,
+            previous_locked_until,
+            locked_until: new_locked_until,
+        }
+        .emit();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn locks_only_get_longer_and_keep_the_grant_id() {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    use crate::derive_grant_id;
+
+    let owner: PublicKey = "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+        .parse()
+        .unwrap();
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(owner.clone())
+        .block_timestamp(1)
+        .build());
+
+    let mut registry = FractalRegistry::default();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+
+    registry
+        .insert_grant(
+            grantee.clone(),
+            "A1".into(),
+            Some(100),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    let grant_id = derive_grant_id(&registry.grants_for(grantee.clone(), "A1".into()).unwrap()[0]);
+    let root = registry.grants_root();
+
+    assert_eq!(
+        registry.extend_lock(grant_id.clone(), 100),
+        Err(RegistryError::LockNotExtended)
+    );
+    registry.extend_lock(grant_id.clone(), 200).unwrap();
+
+    let grants = registry.grants_for(grantee.clone(), "A1".into()).unwrap();
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].locked_until, 200);
+    assert_eq!(derive_grant_id(&grants[0]), grant_id);
+    assert_ne!(registry.grants_root(), root);
+    assert_eq!(registry.grant_lock_revision(grant_id.clone()), 1);
+
+    // Signed extensions have to follow the latest revision, whoever made it.
+    assert_eq!(
+        registry.extend_lock_by_signature(
+            owner,
+            grant_id.clone(),
+            300,
+            1,
+            Nonce([0; 32]),
+            Ed25519Signature([0; 64]),
+        ),
+        Err(RegistryError::InvalidLockRevision)
+    );
+
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(grantee)
+        .block_timestamp(1)
+        .build());
+    assert_eq!(
+        registry.extend_lock(grant_id, 300),
+        Err(RegistryError::NotOwner)
+    );
+}
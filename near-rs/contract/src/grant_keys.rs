@@ -0,0 +1,194 @@
+// Content keys escrowed per grant. The owner encrypts the data's key for the grantee off-chain and leaves it here,
+// so the grantee doesn't need the owner around to start reading. Keys go away with their grants, but their revisions
+// don't: a grant given again gets the same id, and old signatures mustn't work on it.
+
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{env, near_bindgen, PublicKey};
+
+use crate::{
+    nep413_verify, set_grant_key_message, Ed25519Signature, FractalRegistry, FractalRegistryEvents,
+    FractalRegistryExt, Nonce, RegistryError, MAX_GRANT_KEY_LENGTH,
+};
+
+#[near_bindgen]
+impl FractalRegistry {
+    // Also how the owner rotates the key: setting it again replaces it.
+    #[handle_result]
+    pub fn set_grant_key(
+        &mut self,
+        grant_id: String,
+        encrypted_key: Base64VecU8,
+    ) -> Result<(), RegistryError> {
+        let revision = self.grant_key_revision(grant_id.clone()) + 1;
+
+        self._set_grant_key(
+            env::signer_account_pk(),
+            grant_id,
+            encrypted_key.0,
+            revision,
+        )
+    }
+
+    pub fn set_grant_key_by_signature_message(
+        &self,
+        owner: PublicKey,
+        grant_id: String,
+        encrypted_key: Base64VecU8,
+        revision: u32,
+    ) -> String {
+        set_grant_key_message(&owner, &grant_id, &encrypted_key.0, revision)
+    }
+
+    // `revision` has to be one more than `grant_key_revision(grant_id)`.
+    #[handle_result]
+    pub fn set_grant_key_by_signature(
+        &mut self,
+        owner: PublicKey,
+        grant_id: String,
+        encrypted_key: Base64VecU8,
+        revision: u32,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
+        if self.grant_key_revision(grant_id.clone()).checked_add(1) != Some(revision) {
+            return Err(RegistryError::InvalidGrantKeyRevision);
+        }
+
+        nep413_verify(
+            set_grant_key_message(&owner, &grant_id, &encrypted_key.0, revision),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &owner,
+        )?;
+
+        self._set_grant_key(owner, grant_id, encrypted_key.0, revision)
+    }
+
+    pub fn get_grant_key(&self, grant_id: String) -> Option<Base64VecU8> {
+        self.grant_keys
+            .get(&grant_id)
+            .map(|encrypted_key| Base64VecU8(encrypted_key.clone()))
+    }
+
+    // How many times the grant's key was set. Both direct and signed updates count.
+    pub fn grant_key_revision(&self, grant_id: String) -> u32 {
+        self.grant_key_revisions
+            .get(&grant_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn _set_grant_key(
+        &mut self,
+        owner: PublicKey,
+        grant_id: String,
+        encrypted_key: Vec<u8>,
+        revision: u32,
+    ) -> Result<(), RegistryError> {
+        let grant = self
+            .grants_by_id
+            .get(&grant_id)
+            .ok_or(RegistryError::GrantNotFound)?;
+
+        if grant.owner != owner {
+            return Err(RegistryError::NotOwner);
+        }
+
+        if encrypted_key.len() > MAX_GRANT_KEY_LENGTH {
+            return Err(RegistryError::GrantKeyTooLarge);
+        }
+
+        let grantee = grant.grantee.clone();
+        self.grant_keys.insert(grant_id.clone(), encrypted_key);
+        self.grant_key_revisions.insert(grant_id.clone(), revision);
+
+        FractalRegistryEvents::GrantKeySet {
+            grant_id,
+            owner,
+            grantee,
+        }
+        .emit();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn grant_keys_are_owner_set_and_go_with_their_grant() {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    use crate::derive_grant_id;
+
+    let owner: PublicKey = "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+        .parse()
+        .unwrap();
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(owner.clone())
+        .block_timestamp(1)
+        .build());
+
+    let mut registry = FractalRegistry::default();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+
+    registry
+        .insert_grant(grantee.clone(), "A1".into(), None, None, None, None, None)
+        .unwrap();
+    let grant_id = derive_grant_id(&registry.grants_for(grantee.clone(), "A1".into()).unwrap()[0]);
+
+    assert_eq!(registry.get_grant_key(grant_id.clone()), None);
+
+    registry
+        .set_grant_key(grant_id.clone(), Base64VecU8(vec![1; 48]))
+        .unwrap();
+    registry
+        .set_grant_key(grant_id.clone(), Base64VecU8(vec![2; 48]))
+        .unwrap();
+    assert_eq!(
+        registry.get_grant_key(grant_id.clone()),
+        Some(Base64VecU8(vec![2; 48]))
+    );
+    assert_eq!(registry.grant_key_revision(grant_id.clone()), 2);
+
+    // Signed updates have to follow the latest revision, so the first key can't come back.
+    assert_eq!(
+        registry.set_grant_key_by_signature(
+            owner.clone(),
+            grant_id.clone(),
+            Base64VecU8(vec![1; 48]),
+            1,
+            Nonce([0; 32]),
+            Ed25519Signature([0; 64]),
+        ),
+        Err(RegistryError::InvalidGrantKeyRevision)
+    );
+
+    assert_eq!(
+        registry.set_grant_key(
+            grant_id.clone(),
+            Base64VecU8(vec![0; MAX_GRANT_KEY_LENGTH + 1])
+        ),
+        Err(RegistryError::GrantKeyTooLarge)
+    );
+    assert_eq!(
+        registry.set_grant_key("not a grant".into(), Base64VecU8(vec![])),
+        Err(RegistryError::GrantNotFound)
+    );
+
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(grantee.clone())
+        .block_timestamp(1)
+        .build());
+    assert_eq!(
+        registry.set_grant_key(grant_id.clone(), Base64VecU8(vec![3; 48])),
+        Err(RegistryError::NotOwner)
+    );
+
+    registry.renounce_grant(owner, "A1".into()).unwrap();
+    assert_eq!(registry.get_grant_key(grant_id.clone()), None);
+    assert_eq!(registry.grant_key_revision(grant_id), 2);
+}
@@ -0,0 +1,328 @@
+// Owners' allow and deny lists of grantees, checked on every grant insert, relayed ones included. A denied grantee
+// can't get a grant even if they're also allowed.
+//
+// Whether the allow list is on is stored apart from who's on it. Allowing a grantee turns it on, and only
+// `set_allow_list_enabled` turns it off again, so emptying it doesn't let everyone back in by accident.
+
+use near_sdk::store::LookupMap;
+use near_sdk::{env, near_bindgen, PublicKey};
+
+use crate::{
+    nep413_verify, set_allow_list_enabled_message, update_grantee_list_message, Ed25519Signature,
+    FractalRegistry, FractalRegistryEvents, FractalRegistryExt, GranteeList, Nonce, RegistryError,
+    MAX_GRANTEE_LIST_LENGTH,
+};
+
+impl FractalRegistry {
+    pub(crate) fn check_grantee_listed(
+        &self,
+        owner: &PublicKey,
+        grantee: &PublicKey,
+    ) -> Result<(), RegistryError> {
+        if self
+            .grantee_list(owner.clone(), GranteeList::Deny)
+            .contains(grantee)
+        {
+            return Err(RegistryError::GranteeDenied);
+        }
+
+        if self.allow_list_enabled(owner.clone())
+            && !self
+                .grantee_list(owner.clone(), GranteeList::Allow)
+                .contains(grantee)
+        {
+            return Err(RegistryError::GranteeNotAllowed);
+        }
+
+        Ok(())
+    }
+
+    fn grantee_lists_mut(
+        &mut self,
+        list: GranteeList,
+    ) -> &mut LookupMap<PublicKey, Vec<PublicKey>> {
+        match list {
+            GranteeList::Allow => &mut self.grantee_allow_lists,
+            GranteeList::Deny => &mut self.grantee_deny_lists,
+        }
+    }
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    // Adds `grantee` to the signer's `list` or, when not `listed`, takes them off it.
+    #[handle_result]
+    pub fn update_grantee_list(
+        &mut self,
+        list: GranteeList,
+        grantee: PublicKey,
+        listed: bool,
+    ) -> Result<(), RegistryError> {
+        let owner = env::signer_account_pk();
+        let revision = self.grantee_list_revision(owner.clone()) + 1;
+
+        self._update_grantee_list(owner, list, grantee, listed, revision)
+    }
+
+    pub fn update_grantee_list_by_signature_message(
+        &self,
+        owner: PublicKey,
+        list: GranteeList,
+        grantee: PublicKey,
+        listed: bool,
+        revision: u32,
+    ) -> String {
+        update_grantee_list_message(&owner, list, &grantee, listed, revision)
+    }
+
+    // `revision` has to be one more than `grantee_list_revision(owner)`.
+    #[handle_result]
+    pub fn update_grantee_list_by_signature(
+        &mut self,
+        owner: PublicKey,
+        list: GranteeList,
+        grantee: PublicKey,
+        listed: bool,
+        revision: u32,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
+        if self.grantee_list_revision(owner.clone()).checked_add(1) != Some(revision) {
+            return Err(RegistryError::InvalidListRevision);
+        }
+
+        nep413_verify(
+            update_grantee_list_message(&owner, list, &grantee, listed, revision),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &owner,
+        )?;
+
+        self._update_grantee_list(owner, list, grantee, listed, revision)
+    }
+
+    // Turns the signer's allow list on or off, keeping who's on it.
+    pub fn set_allow_list_enabled(&mut self, enabled: bool) {
+        let owner = env::signer_account_pk();
+        let revision = self.grantee_list_revision(owner.clone()) + 1;
+
+        self._set_allow_list_enabled(owner, enabled, revision)
+    }
+
+    pub fn set_allow_list_enabled_by_signature_message(
+        &self,
+        owner: PublicKey,
+        enabled: bool,
+        revision: u32,
+    ) -> String {
+        set_allow_list_enabled_message(&owner, enabled, revision)
+    }
+
+    // `revision` has to be one more than `grantee_list_revision(owner)`.
+    #[handle_result]
+    pub fn set_allow_list_enabled_by_signature(
+        &mut self,
+        owner: PublicKey,
+        enabled: bool,
+        revision: u32,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
+        if self.grantee_list_revision(owner.clone()).checked_add(1) != Some(revision) {
+            return Err(RegistryError::InvalidListRevision);
+        }
+
+        nep413_verify(
+            set_allow_list_enabled_message(&owner, enabled, revision),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &owner,
+        )?;
+
+        self._set_allow_list_enabled(owner, enabled, revision);
+
+        Ok(())
+    }
+
+    pub fn allow_list_enabled(&self, owner: PublicKey) -> bool {
+        self.grantee_allow_lists_enabled
+            .get(&owner)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    pub fn grantee_list(&self, owner: PublicKey, list: GranteeList) -> Vec<PublicKey> {
+        let lists = match list {
+            GranteeList::Allow => &self.grantee_allow_lists,
+            GranteeList::Deny => &self.grantee_deny_lists,
+        };
+
+        lists.get(&owner).cloned().unwrap_or_default()
+    }
+
+    // How many times `owner` updated their lists. Both direct and signed updates count.
+    pub fn grantee_list_revision(&self, owner: PublicKey) -> u32 {
+        self.grantee_list_revisions
+            .get(&owner)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn _update_grantee_list(
+        &mut self,
+        owner: PublicKey,
+        list: GranteeList,
+        grantee: PublicKey,
+        listed: bool,
+        revision: u32,
+    ) -> Result<(), RegistryError> {
+        let grantees = self
+            .grantee_lists_mut(list)
+            .entry(owner.clone())
+            .or_default();
+
+        if listed && !grantees.contains(&grantee) {
+            if grantees.len() >= MAX_GRANTEE_LIST_LENGTH {
+                return Err(RegistryError::GranteeListFull);
+            }
+
+            grantees.push(grantee.clone());
+        } else if !listed {
+            grantees.retain(|listed_grantee| *listed_grantee != grantee);
+        }
+
+        if list == GranteeList::Allow && listed {
+            self.grantee_allow_lists_enabled.insert(owner.clone(), true);
+        }
+
+        self.grantee_list_revisions.insert(owner.clone(), revision);
+
+        FractalRegistryEvents::GranteeListUpdated {
+            owner,
+            list,
+            grantee,
+            listed,
+            revision,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    fn _set_allow_list_enabled(&mut self, owner: PublicKey, enabled: bool, revision: u32) {
+        self.grantee_allow_lists_enabled
+            .insert(owner.clone(), enabled);
+        self.grantee_list_revisions.insert(owner.clone(), revision);
+
+        FractalRegistryEvents::AllowListEnabled {
+            owner,
+            enabled,
+            revision,
+        }
+        .emit();
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn denied_and_unlisted_grantees_cant_get_grants() {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    testing_env!(VMContextBuilder::new().build());
+
+    let mut registry = FractalRegistry::default();
+    let owner = env::signer_account_pk();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let other_grantee: PublicKey = "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+        .parse()
+        .unwrap();
+    let insert = |registry: &mut FractalRegistry, grantee: &PublicKey, data_id: &str| {
+        registry.insert_grant(
+            grantee.clone(),
+            data_id.into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+
+    registry
+        .update_grantee_list(GranteeList::Deny, grantee.clone(), true)
+        .unwrap();
+    assert_eq!(
+        insert(&mut registry, &grantee, "A1"),
+        Err(RegistryError::GranteeDenied)
+    );
+
+    // Denying wins over allowing.
+    registry
+        .update_grantee_list(GranteeList::Allow, grantee.clone(), true)
+        .unwrap();
+    assert_eq!(
+        insert(&mut registry, &grantee, "A1"),
+        Err(RegistryError::GranteeDenied)
+    );
+
+    registry
+        .update_grantee_list(GranteeList::Deny, grantee.clone(), false)
+        .unwrap();
+    insert(&mut registry, &grantee, "A1").unwrap();
+    assert_eq!(
+        insert(&mut registry, &other_grantee, "A1"),
+        Err(RegistryError::GranteeNotAllowed)
+    );
+
+    assert_eq!(
+        registry.grantee_list(owner.clone(), GranteeList::Deny),
+        vec![]
+    );
+    assert_eq!(
+        registry.grantee_list(owner.clone(), GranteeList::Allow),
+        vec![grantee.clone()]
+    );
+    assert_eq!(registry.grantee_list_revision(owner.clone()), 3);
+
+    // Emptying the allow list leaves it on, so it allows nobody.
+    registry
+        .update_grantee_list(GranteeList::Allow, grantee.clone(), false)
+        .unwrap();
+    assert!(registry.allow_list_enabled(owner.clone()));
+    assert_eq!(
+        insert(&mut registry, &other_grantee, "A1"),
+        Err(RegistryError::GranteeNotAllowed)
+    );
+
+    registry.set_allow_list_enabled(false);
+    insert(&mut registry, &other_grantee, "A1").unwrap();
+
+    // Allowing someone turns it back on.
+    registry
+        .update_grantee_list(GranteeList::Allow, grantee.clone(), true)
+        .unwrap();
+    assert_eq!(
+        insert(&mut registry, &other_grantee, "A2"),
+        Err(RegistryError::GranteeNotAllowed)
+    );
+    assert_eq!(registry.grantee_list_revision(owner.clone()), 6);
+
+    // Signed updates have to follow the latest revision, whoever made it.
+    assert_eq!(
+        registry.update_grantee_list_by_signature(
+            owner,
+            GranteeList::Deny,
+            grantee,
+            true,
+            6,
+            Nonce([0; 32]),
+            Ed25519Signature([0; 64]),
+        ),
+        Err(RegistryError::InvalidListRevision)
+    );
+}
@@ -0,0 +1,206 @@
+// Who's behind a grantee key, as the grantee tells it, so wallets have more to show owners than `ed25519:...`. Only
+// the contract account can mark a profile verified, and it can also refuse grants to grantees without one.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, NearSchema, PublicKey, Timestamp};
+
+use crate::{
+    validate_grantee_profile, ContentHash, FractalRegistry, FractalRegistryEvents,
+    FractalRegistryExt, RegistryError,
+};
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GranteeProfile {
+    pub name: String,
+    pub url: String,
+    pub logo_hash: Option<ContentHash>,
+    pub privacy_policy_hash: Option<ContentHash>,
+    pub verified: bool,
+    pub updated_at: Timestamp,
+}
+
+impl FractalRegistry {
+    pub(crate) fn check_grantee_registered(
+        &self,
+        grantee: &PublicKey,
+    ) -> Result<(), RegistryError> {
+        if self.require_registered_grantees && !self.grantee_profiles.contains_key(grantee) {
+            return Err(RegistryError::GranteeNotRegistered);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn require_contract_account(&self) -> Result<(), RegistryError> {
+        if env::predecessor_account_id() != env::current_account_id() {
+            return Err(RegistryError::NotContractAccount);
+        }
+
+        Ok(())
+    }
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    // Registers (or updates) the signer's own profile. Updating it drops `verified`: what was vetted isn't what's
+    // there anymore.
+    #[handle_result]
+    pub fn register_grantee_profile(
+        &mut self,
+        name: String,
+        url: String,
+        logo_hash: Option<ContentHash>,
+        privacy_policy_hash: Option<ContentHash>,
+    ) -> Result<(), RegistryError> {
+        validate_grantee_profile(&name, &url)?;
+
+        let grantee = env::signer_account_pk();
+
+        self.grantee_profiles.insert(
+            grantee.clone(),
+            GranteeProfile {
+                name: name.clone(),
+                url: url.clone(),
+                logo_hash,
+                privacy_policy_hash,
+                verified: false,
+                updated_at: env::block_timestamp(),
+            },
+        );
+
+        FractalRegistryEvents::GranteeProfileRegistered {
+            grantee,
+            name,
+            url,
+            logo_hash,
+            privacy_policy_hash,
+        }
+        .emit();
+
+        Ok(())
+    }
+
+    pub fn grantee_profile(&self, grantee: PublicKey) -> Option<GranteeProfile> {
+        self.grantee_profiles.get(&grantee).cloned()
+    }
+
+    #[handle_result]
+    pub fn set_grantee_verified(
+        &mut self,
+        grantee: PublicKey,
+        verified: bool,
+    ) -> Result<(), RegistryError> {
+        self.require_contract_account()?;
+
+        let profile = self
+            .grantee_profiles
+            .get_mut(&grantee)
+            .ok_or(RegistryError::GranteeNotRegistered)?;
+        profile.verified = verified;
+
+        FractalRegistryEvents::GranteeVerified { grantee, verified }.emit();
+
+        Ok(())
+    }
+
+    pub fn require_registered_grantees(&self) -> bool {
+        self.require_registered_grantees
+    }
+
+    // Grants already inserted stay either way.
+    #[handle_result]
+    pub fn set_require_registered_grantees(&mut self, required: bool) -> Result<(), RegistryError> {
+        self.require_contract_account()?;
+
+        self.require_registered_grantees = required;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::testing_env;
+
+    use super::*;
+
+    fn grantee() -> PublicKey {
+        "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+            .parse()
+            .unwrap()
+    }
+
+    fn called_by_contract_account(yes: bool) {
+        let predecessor = if yes { accounts(0) } else { accounts(1) };
+
+        testing_env!(VMContextBuilder::new()
+            .current_account_id(accounts(0))
+            .predecessor_account_id(predecessor)
+            .signer_account_pk(grantee())
+            .build());
+    }
+
+    #[test]
+    fn updating_a_profile_drops_verified() {
+        let mut registry = FractalRegistry::default();
+        called_by_contract_account(false);
+
+        assert_eq!(
+            registry.register_grantee_profile(
+                "Acme".into(),
+                "http://acme.example".into(),
+                None,
+                None
+            ),
+            Err(RegistryError::InvalidGranteeProfile)
+        );
+        assert_eq!(
+            registry.set_grantee_verified(grantee(), true),
+            Err(RegistryError::NotContractAccount)
+        );
+
+        registry
+            .register_grantee_profile("Acme".into(), "https://acme.example".into(), None, None)
+            .unwrap();
+
+        called_by_contract_account(true);
+        registry.set_grantee_verified(grantee(), true).unwrap();
+        assert!(registry.grantee_profile(grantee()).unwrap().verified);
+
+        registry
+            .register_grantee_profile("Acme Inc".into(), "https://acme.example".into(), None, None)
+            .unwrap();
+        let profile = registry.grantee_profile(grantee()).unwrap();
+        assert_eq!(profile.name, "Acme Inc");
+        assert!(!profile.verified);
+    }
+
+    #[test]
+    fn grants_can_require_registered_grantees() {
+        let mut registry = FractalRegistry::default();
+        let insert = |registry: &mut FractalRegistry, data_id: &str| {
+            registry.insert_grant(grantee(), data_id.into(), None, None, None, None, None)
+        };
+
+        called_by_contract_account(false);
+        assert_eq!(
+            registry.set_require_registered_grantees(true),
+            Err(RegistryError::NotContractAccount)
+        );
+
+        called_by_contract_account(true);
+        registry.set_require_registered_grantees(true).unwrap();
+        assert_eq!(
+            insert(&mut registry, "A1"),
+            Err(RegistryError::GranteeNotRegistered)
+        );
+
+        registry
+            .register_grantee_profile("Acme".into(), "https://acme.example".into(), None, None)
+            .unwrap();
+        insert(&mut registry, "A1").unwrap();
+    }
+}
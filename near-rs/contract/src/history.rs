@@ -0,0 +1,77 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{near_bindgen, NearSchema, PublicKey, Timestamp};
+
+use crate::{DeletionReason, FractalRegistry, FractalRegistryExt, Grant};
+
+const DEFAULT_HISTORY_LIMIT: u32 = 100;
+
+// What's left of a grant after it's deleted, so we can still tell when (and by whom) access was revoked.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, NearSchema, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantTombstone {
+    pub(crate) grant: Grant,
+    pub(crate) deleted_at: Timestamp,
+    pub(crate) deleted_by: PublicKey,
+    pub(crate) reason: DeletionReason,
+}
+
+impl FractalRegistry {
+    pub(crate) fn record_tombstone(&mut self, tombstone: GrantTombstone) {
+        let tombstone_id = self.grant_tombstones.len();
+
+        self.tombstone_ids_by_owner
+            .entry(tombstone.grant.owner.clone())
+            .or_default()
+            .push(tombstone_id);
+
+        self.tombstone_ids_by_grantee
+            .entry(tombstone.grant.grantee.clone())
+            .or_default()
+            .push(tombstone_id);
+
+        self.grant_tombstones.push(tombstone);
+    }
+
+    fn tombstones_from_ids<'a>(&self, ids: impl Iterator<Item = &'a u32>) -> Vec<GrantTombstone> {
+        ids.map(|id| self.grant_tombstones.get(*id).unwrap().clone())
+            .collect()
+    }
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    pub fn grant_history(
+        &self,
+        owner: PublicKey,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<GrantTombstone> {
+        let Some(ids) = self.tombstone_ids_by_owner.get(&owner) else {
+            return vec![];
+        };
+
+        self.tombstones_from_ids(
+            ids.iter()
+                .skip(from.unwrap_or(0) as usize)
+                .take(limit.unwrap_or(DEFAULT_HISTORY_LIMIT) as usize),
+        )
+    }
+
+    pub fn revocations_for(
+        &self,
+        grantee: PublicKey,
+        from: Option<u32>,
+        limit: Option<u32>,
+    ) -> Vec<GrantTombstone> {
+        let Some(ids) = self.tombstone_ids_by_grantee.get(&grantee) else {
+            return vec![];
+        };
+
+        self.tombstones_from_ids(
+            ids.iter()
+                .skip(from.unwrap_or(0) as usize)
+                .take(limit.unwrap_or(DEFAULT_HISTORY_LIMIT) as usize),
+        )
+    }
+}
@@ -1,13 +1,50 @@
 // The `#[near_bindgen]` for `impl FractalRegistry` was triggering this, and I couldn't find a way to suppress it.
 #![allow(clippy::too_many_arguments)]
+extern crate access_grants_core;
 extern crate near_sdk;
 
 use std::convert::TryInto;
 
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::serde::Serialize;
-use near_sdk::store::LookupMap;
-use near_sdk::{env, near_bindgen, require, CurveType, EpochHeight, PublicKey};
+use near_sdk::store::{LookupMap, Vector};
+use near_sdk::{
+    env, near_bindgen, CurveType, Duration, EpochHeight, NearSchema, PublicKey, Timestamp,
+};
+
+pub use access_grants_core::merkle::{verify_grant_proof, GrantProof, MerkleLeaf};
+pub use access_grants_core::{
+    consent_hash, delete_grant_message, delete_prefix_grant_message, derive_grant_id,
+    extend_lock_message, insert_grant_message, insert_prefix_grant_message, nep413_hashed_payload,
+    publish_encryption_key_message, renounce_grant_message, revoke_all_message,
+    set_allow_list_enabled_message, set_grant_key_message, update_grantee_list_message,
+    validate_grantee_profile, validate_purpose, AccessMode, AccessScope, ChangeKind, ConsentHash,
+    ContentHash, DeletionReason, Ed25519Signature, EncryptionKey, Grant, GrantScope, GranteeList,
+    LockPolicy, Nep413Payload, Nonce, RegistryError, GRANT_MESSAGE_RECIPIENT,
+    MAX_GRANTEE_LIST_LENGTH, MAX_GRANT_KEY_LENGTH, MAX_LOCK_POLICIES, NEP413_TAG,
+};
+
+mod access_requests;
+mod changes;
+mod encryption_keys;
+mod extend_lock;
+mod grant_keys;
+mod grantee_lists;
+mod grantee_profiles;
+mod history;
+mod lock_policies;
+mod merkle;
+mod migrate;
+mod multi;
+mod renounce;
+mod revoke;
+pub use access_requests::AccessRequest;
+pub use changes::{ChangesPage, GrantChange};
+pub use encryption_keys::GranteeEncryptionKey;
+pub use grantee_profiles::GranteeProfile;
+pub use history::GrantTombstone;
+pub use merkle::MerkleNode;
+pub use multi::GrantsForKey;
+pub use revoke::{RevokeAllResult, SignedRevokeAll};
 
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -17,15 +54,42 @@ pub struct FractalRegistry {
     pub grant_ids_by_owner: LookupMap<PublicKey, Vec<String>>,
     pub grant_ids_by_grantee: LookupMap<PublicKey, Vec<String>>,
     pub grant_ids_by_data_id: LookupMap<String, Vec<String>>,
-}
+    pub grant_ids_by_data_id_prefix: LookupMap<String, Vec<String>>,
+
+    pub grant_keys: LookupMap<String, Vec<u8>>,
+    pub grant_key_revisions: LookupMap<String, u32>,
+    pub grant_lock_revisions: LookupMap<String, u32>,
+    pub grant_renounce_revisions: LookupMap<(PublicKey, PublicKey, String), u32>,
+    pub encryption_keys_by_grantee: LookupMap<PublicKey, Vec<GranteeEncryptionKey>>,
+
+    pub grantee_profiles: LookupMap<PublicKey, GranteeProfile>,
+    pub require_registered_grantees: bool,
+
+    pub grantee_allow_lists: LookupMap<PublicKey, Vec<PublicKey>>,
+    pub grantee_allow_lists_enabled: LookupMap<PublicKey, bool>,
+    pub grantee_deny_lists: LookupMap<PublicKey, Vec<PublicKey>>,
+    pub grantee_list_revisions: LookupMap<PublicKey, u32>,
+
+    pub lock_policies_by_grantee: LookupMap<PublicKey, Vec<LockPolicy>>,
+
+    pub signed_revoke_alls: LookupMap<PublicKey, SignedRevokeAll>,
+
+    pub grant_tombstones: Vector<GrantTombstone>,
+    pub tombstone_ids_by_owner: LookupMap<PublicKey, Vec<u32>>,
+    pub tombstone_ids_by_grantee: LookupMap<PublicKey, Vec<u32>>,
+
+    pub last_change_seq: u64,
+    pub recent_changes: LookupMap<u64, GrantChange>,
+
+    pub merkle_nodes: LookupMap<Vec<u8>, MerkleNode>,
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone)]
-#[serde(crate = "near_sdk::serde")]
-pub struct Grant {
-    owner: PublicKey,
-    grantee: PublicKey,
-    data_id: String,
-    locked_until: EpochHeight,
+    pub access_requests: LookupMap<u64, AccessRequest>,
+    pub access_request_ids_by_owner: LookupMap<PublicKey, Vec<u64>>,
+    pub access_request_ids_by_grantee: LookupMap<PublicKey, Vec<u64>>,
+    pub last_access_request_id: u64,
+    pub access_request_ttl: Duration,
+
+    pub legacy_grants_left: u32,
 }
 
 #[cfg(test)]
@@ -41,166 +105,275 @@ fn derive_grant_id_example() {
             .parse()
             .unwrap(),
         data_id: "some data".into(),
+        scope: GrantScope::Exact,
         locked_until: 1337,
+        purpose: None,
+        access: None,
+        consent_hash: None,
+        valid_from: 0,
     };
 
     assert_eq!(
-        "8031eff696fa15a7e4c69530a1d8b634faab8d512fde219b92aae0082adb8606",
+        "34c755f71143a5dd4a9c3c0425f3d573c86579b586e72dd8ef04e1b41606c657",
         derive_grant_id(&grant)
     );
 }
 
-pub fn derive_grant_id(grant: &Grant) -> String {
-    let id = format!(
-        "{}{}{}{}",
-        Into::<String>::into(&grant.owner),
-        Into::<String>::into(&grant.grantee),
-        grant.data_id,
-        grant.locked_until,
-    );
-
-    hex::encode(env::keccak256(id.as_bytes()))
-}
-
 impl Default for FractalRegistry {
     fn default() -> Self {
         let grants_by_id = LookupMap::new(b"g");
         let grant_ids_by_owner = LookupMap::new(b"h");
         let grant_ids_by_grantee = LookupMap::new(b"i");
         let grant_ids_by_data_id = LookupMap::new(b"j");
+        let grant_ids_by_data_id_prefix = LookupMap::new(b"p");
+
+        let grant_keys = LookupMap::new(b"s");
+        let grant_key_revisions = LookupMap::new(b"a");
+        let grant_lock_revisions = LookupMap::new(b"b");
+        let grant_renounce_revisions = LookupMap::new(b"d");
+        let encryption_keys_by_grantee = LookupMap::new(b"t");
+
+        let grantee_profiles = LookupMap::new(b"u");
+
+        let grantee_allow_lists = LookupMap::new(b"v");
+        let grantee_allow_lists_enabled = LookupMap::new(b"e");
+        let grantee_deny_lists = LookupMap::new(b"w");
+        let grantee_list_revisions = LookupMap::new(b"x");
+
+        let lock_policies_by_grantee = LookupMap::new(b"y");
+
+        let signed_revoke_alls = LookupMap::new(b"z");
+
+        let grant_tombstones = Vector::new(b"k");
+        let tombstone_ids_by_owner = LookupMap::new(b"l");
+        let tombstone_ids_by_grantee = LookupMap::new(b"m");
+
+        let recent_changes = LookupMap::new(b"n");
+
+        let merkle_nodes = LookupMap::new(b"o");
+
+        let access_requests = LookupMap::new(b"q");
+        let access_request_ids_by_owner = LookupMap::new(b"r");
+        let access_request_ids_by_grantee = LookupMap::new(b"c");
 
         Self {
             grants_by_id,
             grant_ids_by_owner,
             grant_ids_by_grantee,
             grant_ids_by_data_id,
+            grant_ids_by_data_id_prefix,
+            grant_keys,
+            grant_key_revisions,
+            grant_lock_revisions,
+            grant_renounce_revisions,
+            encryption_keys_by_grantee,
+            grantee_profiles,
+            require_registered_grantees: false,
+            grantee_allow_lists,
+            grantee_allow_lists_enabled,
+            grantee_deny_lists,
+            grantee_list_revisions,
+            lock_policies_by_grantee,
+            signed_revoke_alls,
+            grant_tombstones,
+            tombstone_ids_by_owner,
+            tombstone_ids_by_grantee,
+            last_change_seq: 0,
+            recent_changes,
+            merkle_nodes,
+            access_requests,
+            access_request_ids_by_owner,
+            access_request_ids_by_grantee,
+            last_access_request_id: 0,
+            access_request_ttl: access_requests::DEFAULT_ACCESS_REQUEST_TTL,
+            legacy_grants_left: 0,
         }
     }
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Clone)]
-#[serde(crate = "near_sdk::serde")]
-struct Nep413Payload {
-    pub message: String,
-    pub nonce: [u8; 32],
-    pub recipient: String,
-    #[serde(rename = "callbackUrl")]
-    pub callback_url: Option<String>,
+pub fn public_key_bytes_ref(public_key: &PublicKey) -> Result<&[u8; 32], RegistryError> {
+    // First byte is the curve type.
+    public_key.as_bytes()[1..]
+        .try_into()
+        .map_err(|_| RegistryError::InvalidPublicKey)
 }
 
-const NEP413_TAG: u32 = 2147484061; // 2**31 + 413
-fn nep413_hashed_payload(payload: &Nep413Payload) -> [u8; 32] {
-    let mut writer = vec![];
+#[near_bindgen(event_json(standard = "FractalRegistry"))]
+#[derive(NearSchema)]
+pub enum FractalRegistryEvents {
+    #[event_version("4")]
+    GrantInserted {
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id: String,
+        scope: GrantScope,
+        locked_until: EpochHeight,
+        purpose: Option<String>,
+        access: Option<AccessScope>,
+        consent_hash: Option<ConsentHash>,
+        valid_from: Timestamp,
+    },
 
-    borsh::to_writer(&mut writer, &NEP413_TAG).expect("Can't borsh encode NEP413_TAG");
-    borsh::to_writer(&mut writer, payload).expect("Can't borsh encode payload");
+    #[event_version("1")]
+    GrantDeleted {
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id: String,
+        scope: GrantScope,
+        locked_until: EpochHeight,
+    },
 
-    env::sha256_array(&writer)
-}
+    #[event_version("0")]
+    GrantRenounced {
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id: String,
+        scope: GrantScope,
+        locked_until: EpochHeight,
+    },
 
-// Just so people don't pass in the wrong name for the message.
-macro_rules! u8_to_fixed_length_array {
-    ( $value:expr ) => {
-        __u8_to_fixed_length_array($value, stringify!($value))
-    };
-}
+    #[event_version("0")]
+    GrantLockExtended {
+        grant_id: String,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id: String,
+        scope: GrantScope,
+        previous_locked_until: EpochHeight,
+        locked_until: EpochHeight,
+    },
 
-fn __u8_to_fixed_length_array<'a, const N: usize>(
-    source: &'a [u8],
-    name: &'static str,
-) -> &'a [u8; N] {
-    source.try_into().unwrap_or_else(|_| {
-        panic!(
-            "{} doesn't seem to have exactly {} bytes: {:?}",
-            name, N, source
-        )
-    })
-}
+    #[event_version("0")]
+    GrantKeySet {
+        grant_id: String,
+        owner: PublicKey,
+        grantee: PublicKey,
+    },
 
-#[cfg(test)]
-#[test]
-fn u8_to_fixed_length_array_example_good() {
-    assert_eq!(
-        [1, 2, 3],
-        *u8_to_fixed_length_array!(vec![1, 2, 3].as_slice())
-    );
-}
+    #[event_version("0")]
+    EncryptionKeyPublished {
+        grantee: PublicKey,
+        encryption_key: EncryptionKey,
+        rotation: u32,
+    },
 
-#[cfg(test)]
-#[test]
-#[should_panic(expected = "original.as_slice() doesn't seem to have exactly 1 bytes: [1, 2, 3]")]
-fn u8_to_fixed_length_array_example_bad() {
-    let original = vec![1, 2, 3];
-    let _var_name: [u8; 1] = *u8_to_fixed_length_array!(original.as_slice());
-}
+    #[event_version("0")]
+    GranteeProfileRegistered {
+        grantee: PublicKey,
+        name: String,
+        url: String,
+        logo_hash: Option<ContentHash>,
+        privacy_policy_hash: Option<ContentHash>,
+    },
 
-pub fn public_key_bytes_ref(public_key: &PublicKey) -> &[u8; 32] {
-    // First byte is the curve type.
-    u8_to_fixed_length_array!(&public_key.as_bytes()[1..])
-}
+    #[event_version("0")]
+    GranteeVerified { grantee: PublicKey, verified: bool },
 
-#[near_bindgen(event_json(standard = "FractalRegistry"))]
-pub enum FractalRegistryEvents {
     #[event_version("0")]
-    GrantInserted {
+    GranteeListUpdated {
+        owner: PublicKey,
+        list: GranteeList,
+        grantee: PublicKey,
+        listed: bool,
+        revision: u32,
+    },
+
+    #[event_version("0")]
+    AllowListEnabled {
+        owner: PublicKey,
+        enabled: bool,
+        revision: u32,
+    },
+
+    #[event_version("0")]
+    LockPoliciesSet {
+        grantee: PublicKey,
+        policies: Vec<LockPolicy>,
+    },
+
+    #[event_version("0")]
+    AccessRequested {
+        id: u64,
         owner: PublicKey,
         grantee: PublicKey,
         data_id: String,
-        locked_until: EpochHeight,
+        purpose: String,
+        desired_lock: Option<EpochHeight>,
+        expires_at: Timestamp,
     },
 
     #[event_version("0")]
-    GrantDeleted {
+    AccessRequestApproved {
+        id: u64,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id: String,
+    },
+
+    #[event_version("0")]
+    AccessRequestRejected {
+        id: u64,
         owner: PublicKey,
         grantee: PublicKey,
         data_id: String,
-        locked_until: EpochHeight,
     },
 }
 
 fn nep413_verify(
     message: String,
-    nonce: Vec<u8>,
+    nonce: Nonce,
     recipient: String,
-    signature: Vec<u8>,
+    signature: Ed25519Signature,
     owner: &PublicKey,
-) {
-    require!(
-        owner.curve_type() == CurveType::ED25519,
-        "Only ed25519 keys are supported",
-    );
-
-    // Serde didn't have [u8; 64] implemented, only up to 32. So, I've decided to convert them inside the function.
-    let nonce: [u8; 32] = *u8_to_fixed_length_array!(nonce.as_slice());
-    let signature: [u8; 64] = *u8_to_fixed_length_array!(signature.as_slice());
-
+) -> Result<(), RegistryError> {
+    if owner.curve_type() != CurveType::ED25519 {
+        return Err(RegistryError::UnsupportedKeyType);
+    }
 
     let hashed_payload = nep413_hashed_payload(&Nep413Payload {
-        message: message,
-        nonce,
+        message,
+        nonce: nonce.0,
         recipient,
         callback_url: None,
     });
 
-    require!(
-        env::ed25519_verify(&signature, &hashed_payload, public_key_bytes_ref(owner),),
-        "Signature doesn't match"
-    );
+    if !env::ed25519_verify(&signature.0, &hashed_payload, public_key_bytes_ref(owner)?) {
+        return Err(RegistryError::InvalidSignature);
+    }
+
+    Ok(())
 }
 
 #[near_bindgen]
 impl FractalRegistry {
     pub fn grant_message_recipient(&self) -> String {
-        "idos.network".into()
+        GRANT_MESSAGE_RECIPIENT.into()
     }
 
+    // `purpose` (like `kyc`) and `access` are optional, and only recorded: nothing here enforces them. `valid_from`
+    // holds off access until then, though the grant is there (and counts as a duplicate) right away.
+    #[handle_result]
     pub fn insert_grant(
         &mut self,
         grantee: PublicKey,
         data_id: String,
         locked_until: Option<EpochHeight>,
-    ) {
-        self._insert_grant(env::signer_account_pk(), grantee, data_id, locked_until)
+        purpose: Option<String>,
+        access: Option<AccessScope>,
+        consent_hash: Option<ConsentHash>,
+        valid_from: Option<Timestamp>,
+    ) -> Result<(), RegistryError> {
+        self._insert_grant(
+            env::signer_account_pk(),
+            grantee,
+            data_id,
+            GrantScope::Exact,
+            locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
+        )
     }
 
     pub fn insert_grant_by_signature_message(
@@ -209,43 +382,157 @@ impl FractalRegistry {
         grantee: PublicKey,
         data_id: String,
         locked_until: Option<EpochHeight>,
+        purpose: Option<String>,
+        access: Option<AccessScope>,
+        consent_hash: Option<ConsentHash>,
+        valid_from: Option<Timestamp>,
     ) -> String {
-        format!(
-            "operation: insertGrant\n\
-            owner: {}\n\
-            grantee: {}\n\
-            dataId: {}\n\
-            lockedUntil: {}",
-            Into::<String>::into(&owner),
-            Into::<String>::into(&grantee),
-            data_id,
-            locked_until.unwrap_or(0)
+        insert_grant_message(
+            &owner,
+            &grantee,
+            &data_id,
+            locked_until,
+            purpose.as_deref(),
+            access.as_ref(),
+            consent_hash.as_ref(),
+            valid_from,
         )
     }
 
+    #[handle_result]
     pub fn insert_grant_by_signature(
         &mut self,
         owner: PublicKey,
         grantee: PublicKey,
         data_id: String,
         locked_until: Option<EpochHeight>,
-        nonce: Vec<u8>,
-        signature: Vec<u8>,
-    ) {
+        purpose: Option<String>,
+        access: Option<AccessScope>,
+        consent_hash: Option<ConsentHash>,
+        valid_from: Option<Timestamp>,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
         nep413_verify(
             self.insert_grant_by_signature_message(
                 owner.clone(),
                 grantee.clone(),
                 data_id.clone(),
                 locked_until,
+                purpose.clone(),
+                access.clone(),
+                consent_hash,
+                valid_from,
             ),
             nonce,
             self.grant_message_recipient(),
             signature,
             &owner,
-        );
+        )?;
 
-        self._insert_grant(owner, grantee, data_id, locked_until)
+        self._insert_grant(
+            owner,
+            grantee,
+            data_id,
+            GrantScope::Exact,
+            locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
+        )
+    }
+
+    // A grant for every data id starting with `data_id_prefix`, including ones that don't exist yet.
+    #[handle_result]
+    pub fn insert_prefix_grant(
+        &mut self,
+        grantee: PublicKey,
+        data_id_prefix: String,
+        locked_until: Option<EpochHeight>,
+        purpose: Option<String>,
+        access: Option<AccessScope>,
+        consent_hash: Option<ConsentHash>,
+        valid_from: Option<Timestamp>,
+    ) -> Result<(), RegistryError> {
+        self._insert_grant(
+            env::signer_account_pk(),
+            grantee,
+            data_id_prefix,
+            GrantScope::Prefix,
+            locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
+        )
+    }
+
+    pub fn insert_prefix_grant_by_signature_message(
+        &self,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id_prefix: String,
+        locked_until: Option<EpochHeight>,
+        purpose: Option<String>,
+        access: Option<AccessScope>,
+        consent_hash: Option<ConsentHash>,
+        valid_from: Option<Timestamp>,
+    ) -> String {
+        insert_prefix_grant_message(
+            &owner,
+            &grantee,
+            &data_id_prefix,
+            locked_until,
+            purpose.as_deref(),
+            access.as_ref(),
+            consent_hash.as_ref(),
+            valid_from,
+        )
+    }
+
+    #[handle_result]
+    pub fn insert_prefix_grant_by_signature(
+        &mut self,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id_prefix: String,
+        locked_until: Option<EpochHeight>,
+        purpose: Option<String>,
+        access: Option<AccessScope>,
+        consent_hash: Option<ConsentHash>,
+        valid_from: Option<Timestamp>,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
+        nep413_verify(
+            self.insert_prefix_grant_by_signature_message(
+                owner.clone(),
+                grantee.clone(),
+                data_id_prefix.clone(),
+                locked_until,
+                purpose.clone(),
+                access.clone(),
+                consent_hash,
+                valid_from,
+            ),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &owner,
+        )?;
+
+        self._insert_grant(
+            owner,
+            grantee,
+            data_id_prefix,
+            GrantScope::Prefix,
+            locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
+        )
     }
 
     fn _insert_grant(
@@ -253,21 +540,49 @@ impl FractalRegistry {
         owner: PublicKey,
         grantee: PublicKey,
         data_id: String,
+        scope: GrantScope,
         locked_until: Option<EpochHeight>,
-    ) {
+        purpose: Option<String>,
+        access: Option<AccessScope>,
+        consent_hash: Option<ConsentHash>,
+        valid_from: Option<Timestamp>,
+    ) -> Result<(), RegistryError> {
+        if scope == GrantScope::Prefix && data_id.is_empty() {
+            return Err(RegistryError::EmptyDataIdPrefix);
+        }
+
+        if let Some(purpose) = &purpose {
+            validate_purpose(purpose)?;
+        }
+
+        if let Some(access) = &access {
+            access.validate()?;
+        }
+
+        self.check_grantee_registered(&grantee)?;
+        self.check_grantee_listed(&owner, &grantee)?;
+        self.check_lock_policies(&grantee, &data_id, scope, locked_until.unwrap_or(0))?;
+
         let grant = Grant {
             owner: owner.clone(),
             grantee: grantee.clone(),
             data_id: data_id.clone(),
+            scope,
             locked_until: locked_until.unwrap_or(0),
+            purpose: purpose.clone(),
+            access: access.clone(),
+            consent_hash,
+            valid_from: valid_from.unwrap_or(0),
         };
 
         let grant_id = derive_grant_id(&grant);
 
-        require!(
-            !self.grants_by_id.contains_key(&grant_id),
-            "Grant already exists"
-        );
+        if self.grants_by_id.contains_key(&grant_id) {
+            return Err(RegistryError::GrantAlreadyExists);
+        }
+
+        self.record_change(ChangeKind::Inserted, &grant);
+        self.merkle_insert_grant(&grant_id, &grant);
 
         self.grants_by_id.insert(grant_id.clone(), grant);
 
@@ -281,29 +596,44 @@ impl FractalRegistry {
             .or_default()
             .push(grant_id.clone());
 
-        self.grant_ids_by_data_id
+        self.data_id_index_mut(scope)
             .entry(data_id.clone())
             .or_default()
             .push(grant_id.clone());
 
         let locked_until = locked_until.unwrap_or(0);
+        let valid_from = valid_from.unwrap_or(0);
 
         FractalRegistryEvents::GrantInserted {
             owner,
             grantee,
             data_id,
+            scope,
             locked_until,
+            purpose,
+            access,
+            consent_hash,
+            valid_from,
         }
         .emit();
+
+        Ok(())
     }
 
+    #[handle_result]
     pub fn delete_grant(
         &mut self,
         grantee: PublicKey,
         data_id: String,
         locked_until: Option<EpochHeight>,
-    ) {
-        self._delete_grant(env::signer_account_pk(), grantee, data_id, locked_until)
+    ) -> Result<(), RegistryError> {
+        self._delete_grant(
+            env::signer_account_pk(),
+            grantee,
+            data_id,
+            GrantScope::Exact,
+            locked_until,
+        )
     }
 
     pub fn delete_grant_by_signature_message(
@@ -313,28 +643,19 @@ impl FractalRegistry {
         data_id: String,
         locked_until: Option<EpochHeight>,
     ) -> String {
-        format!(
-            "operation: deleteGrant\n\
-            owner: {}\n\
-            grantee: {}\n\
-            dataId: {}\n\
-            lockedUntil: {}",
-            Into::<String>::into(&owner),
-            Into::<String>::into(&grantee),
-            data_id,
-            locked_until.unwrap_or(0)
-        )
+        delete_grant_message(&owner, &grantee, &data_id, locked_until)
     }
 
+    #[handle_result]
     pub fn delete_grant_by_signature(
         &mut self,
         owner: PublicKey,
         grantee: PublicKey,
         data_id: String,
         locked_until: Option<EpochHeight>,
-        nonce: Vec<u8>,
-        signature: Vec<u8>,
-    ) {
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
         nep413_verify(
             self.delete_grant_by_signature_message(
                 owner.clone(),
@@ -346,9 +667,68 @@ impl FractalRegistry {
             self.grant_message_recipient(),
             signature,
             &owner,
-        );
+        )?;
+
+        self._delete_grant(owner, grantee, data_id, GrantScope::Exact, locked_until)
+    }
+
+    // Only deletes the prefix grant itself; exact grants under the prefix stay.
+    #[handle_result]
+    pub fn delete_prefix_grant(
+        &mut self,
+        grantee: PublicKey,
+        data_id_prefix: String,
+        locked_until: Option<EpochHeight>,
+    ) -> Result<(), RegistryError> {
+        self._delete_grant(
+            env::signer_account_pk(),
+            grantee,
+            data_id_prefix,
+            GrantScope::Prefix,
+            locked_until,
+        )
+    }
 
-        self._delete_grant(owner, grantee, data_id, locked_until)
+    pub fn delete_prefix_grant_by_signature_message(
+        &self,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id_prefix: String,
+        locked_until: Option<EpochHeight>,
+    ) -> String {
+        delete_prefix_grant_message(&owner, &grantee, &data_id_prefix, locked_until)
+    }
+
+    #[handle_result]
+    pub fn delete_prefix_grant_by_signature(
+        &mut self,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id_prefix: String,
+        locked_until: Option<EpochHeight>,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
+        nep413_verify(
+            self.delete_prefix_grant_by_signature_message(
+                owner.clone(),
+                grantee.clone(),
+                data_id_prefix.clone(),
+                locked_until,
+            ),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &owner,
+        )?;
+
+        self._delete_grant(
+            owner,
+            grantee,
+            data_id_prefix,
+            GrantScope::Prefix,
+            locked_until,
+        )
     }
 
     fn _delete_grant(
@@ -356,44 +736,33 @@ impl FractalRegistry {
         owner: PublicKey,
         grantee: PublicKey,
         data_id: String,
+        scope: GrantScope,
         locked_until: Option<EpochHeight>,
-    ) {
-        self.find_grants(
+    ) -> Result<(), RegistryError> {
+        let grants = self.find_grants(
             Some(owner.clone()),
             Some(grantee.clone()),
             Some(data_id.clone()),
-        )
-        .iter()
-        .filter(|grant| match locked_until {
-            None => true,
-            Some(0) => true,
-            Some(locked_until_) => grant.locked_until == locked_until_,
-        })
-        .for_each(|grant| {
-            require!(
-                grant.locked_until < env::block_timestamp(),
-                "Grant is timelocked"
-            );
-
-            let grant_id = derive_grant_id(grant);
-
-            self.grants_by_id.remove(&grant_id);
-
-            self.grant_ids_by_owner
-                .get_mut(&owner)
-                .unwrap_or(&mut vec![])
-                .retain(|id| *id != *grant_id);
-
-            self.grant_ids_by_grantee
-                .get_mut(&grantee)
-                .unwrap_or(&mut vec![])
-                .retain(|id| *id != *grant_id);
-
-            self.grant_ids_by_data_id
-                .get_mut(&data_id)
-                .unwrap_or(&mut vec![])
-                .retain(|id| *id != *grant_id);
-        });
+            None,
+            None,
+        )?;
+
+        // `find_grants` also returns whatever else covers `data_id`, like broader prefix grants.
+        for grant in grants
+            .iter()
+            .filter(|grant| grant.scope == scope && grant.data_id == data_id)
+            .filter(|grant| match locked_until {
+                None => true,
+                Some(0) => true,
+                Some(locked_until_) => grant.locked_until == locked_until_,
+            })
+        {
+            if grant.locked_until >= env::block_timestamp() {
+                return Err(RegistryError::GrantTimelocked);
+            }
+
+            self._remove_grant(grant, owner.clone(), DeletionReason::Deleted);
+        }
 
         let locked_until = locked_until.unwrap_or(0);
 
@@ -401,29 +770,118 @@ impl FractalRegistry {
             owner,
             grantee,
             data_id,
+            scope,
             locked_until,
         }
         .emit();
+
+        Ok(())
+    }
+
+    fn _remove_grant(&mut self, grant: &Grant, deleted_by: PublicKey, reason: DeletionReason) {
+        let grant_id = derive_grant_id(grant);
+
+        self.grants_by_id.remove(&grant_id);
+        self.grant_keys.remove(&grant_id);
+
+        self.grant_ids_by_owner
+            .get_mut(&grant.owner)
+            .unwrap_or(&mut vec![])
+            .retain(|id| *id != *grant_id);
+
+        self.grant_ids_by_grantee
+            .get_mut(&grant.grantee)
+            .unwrap_or(&mut vec![])
+            .retain(|id| *id != *grant_id);
+
+        self.data_id_index_mut(grant.scope)
+            .get_mut(&grant.data_id)
+            .unwrap_or(&mut vec![])
+            .retain(|id| *id != *grant_id);
+
+        self.record_change(ChangeKind::Deleted, grant);
+        self.merkle_remove_grant(&grant_id);
+
+        self.record_tombstone(GrantTombstone {
+            grant: grant.clone(),
+            deleted_at: env::block_timestamp(),
+            deleted_by,
+            reason,
+        });
+    }
+
+    fn data_id_index_mut(&mut self, scope: GrantScope) -> &mut LookupMap<String, Vec<String>> {
+        match scope {
+            GrantScope::Exact => &mut self.grant_ids_by_data_id,
+            GrantScope::Prefix => &mut self.grant_ids_by_data_id_prefix,
+        }
+    }
+
+    // Exact grants for `data_id`, plus prefix grants for every prefix of it (itself included).
+    pub(crate) fn grant_ids_covering(&self, data_id: &str) -> Vec<String> {
+        let mut grant_ids = self
+            .grant_ids_by_data_id
+            .get(data_id)
+            .cloned()
+            .unwrap_or_default();
+
+        for end in (1..=data_id.len()).filter(|&end| data_id.is_char_boundary(end)) {
+            if let Some(prefix_grant_ids) = self.grant_ids_by_data_id_prefix.get(&data_id[..end]) {
+                grant_ids.extend(prefix_grant_ids.iter().cloned());
+            }
+        }
+
+        grant_ids
+    }
+
+    pub fn has_access(&self, grantee: PublicKey, data_id: String) -> bool {
+        self.grants_for(grantee, data_id)
+            .is_ok_and(|grants| !grants.is_empty())
     }
 
-    pub fn grants_for(&self, grantee: PublicKey, data_id: String) -> Vec<Grant> {
-        self.find_grants(None, Some(grantee), Some(data_id))
+    // Whether `text` is what the owner consented to when granting. Always false for grants without a consent hash.
+    #[handle_result]
+    pub fn verify_consent(&self, grant_id: String, text: String) -> Result<bool, RegistryError> {
+        let grant = self
+            .grants_by_id
+            .get(&grant_id)
+            .ok_or(RegistryError::GrantNotFound)?;
+
+        Ok(grant.consent_hash == Some(consent_hash(&text)))
+    }
+
+    // Only grants already active: `find_grants` lists the ones starting later too.
+    #[handle_result]
+    pub fn grants_for(
+        &self,
+        grantee: PublicKey,
+        data_id: String,
+    ) -> Result<Vec<Grant>, RegistryError> {
+        let mut grants = self.find_grants(None, Some(grantee), Some(data_id), None, None)?;
+        grants.retain(|grant| grant.is_active(env::block_timestamp()));
+
+        Ok(grants)
     }
 
+    // `purpose` has to match exactly. `access_mode` keeps grants that allow it: read-write ones allow read-only too.
+    #[handle_result]
     pub fn find_grants(
         &self,
         owner: Option<PublicKey>,
         grantee: Option<PublicKey>,
         data_id: Option<String>,
-    ) -> Vec<Grant> {
+        purpose: Option<String>,
+        access_mode: Option<AccessMode>,
+    ) -> Result<Vec<Grant>, RegistryError> {
         let mut grant_id_searches = Vec::new();
 
-        require!(
-            owner.is_some() || grantee.is_some(),
-            "Required argument: `owner` and/or `grantee`",
-        );
+        if owner.is_none() && grantee.is_none() {
+            return Err(RegistryError::MissingOwnerOrGrantee);
+        }
 
         let empty = vec![];
+        let data_id_grant_ids = data_id.map(|data_id| self.grant_ids_covering(&data_id));
+
         if let Some(owner) = owner {
             grant_id_searches.push(self.grant_ids_by_owner.get(&owner).unwrap_or(&empty));
         }
@@ -432,17 +890,336 @@ impl FractalRegistry {
             grant_id_searches.push(self.grant_ids_by_grantee.get(&grantee).unwrap_or(&empty));
         }
 
-        if let Some(data_id) = data_id {
-            grant_id_searches.push(self.grant_ids_by_data_id.get(&data_id).unwrap_or(&empty));
+        if let Some(grant_ids) = &data_id_grant_ids {
+            grant_id_searches.push(grant_ids);
+        }
+
+        let mut grants = self.grants_in_all(&grant_id_searches)?;
+
+        if let Some(purpose) = purpose {
+            grants.retain(|grant| grant.purpose.as_ref() == Some(&purpose));
+        }
+
+        if let Some(access_mode) = access_mode {
+            grants.retain(|grant| grant.allows(access_mode));
         }
 
+        Ok(grants)
+    }
+
+    // Grants whose ids show up in every one of `grant_id_searches`.
+    pub(crate) fn grants_in_all(
+        &self,
+        grant_id_searches: &[&Vec<String>],
+    ) -> Result<Vec<Grant>, RegistryError> {
+        self.require_migrated()?;
+
         let Some((head, tail)) = grant_id_searches.split_first() else {
-            return vec![];
+            return Ok(vec![]);
         };
 
-        head.iter()
+        Ok(head
+            .iter()
             .filter(|id| tail.iter().all(|s| s.contains(id)))
             .map(|id| self.grants_by_id.get(id).unwrap().clone())
-            .collect()
+            .collect())
     }
 }
+
+#[cfg(test)]
+#[test]
+fn entrypoints_return_registry_errors() {
+    let mut registry = FractalRegistry::default();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let data_id = || String::from("some data");
+
+    registry
+        .insert_grant(
+            grantee.clone(),
+            data_id(),
+            Some(u64::MAX),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    assert_eq!(
+        registry.insert_grant(
+            grantee.clone(),
+            data_id(),
+            Some(u64::MAX),
+            None,
+            None,
+            None,
+            None
+        ),
+        Err(RegistryError::GrantAlreadyExists)
+    );
+    assert_eq!(
+        registry.delete_grant(grantee.clone(), data_id(), None),
+        Err(RegistryError::GrantTimelocked)
+    );
+    assert_eq!(
+        registry.find_grants(None, None, Some(data_id()), None, None),
+        Err(RegistryError::MissingOwnerOrGrantee)
+    );
+    assert_eq!(
+        registry.grant_proof("not hex".into()).unwrap_err(),
+        RegistryError::InvalidGrantId
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn prefix_grants_cover_data_ids_under_them() {
+    near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+        .block_timestamp(1)
+        .build());
+
+    let mut registry = FractalRegistry::default();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+
+    registry
+        .insert_prefix_grant(
+            grantee.clone(),
+            "creds/".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    registry
+        .insert_grant(
+            grantee.clone(),
+            "creds/passport".into(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    assert!(registry.has_access(grantee.clone(), "creds/".into()));
+    assert!(registry.has_access(grantee.clone(), "creds/drivers_license".into()));
+    assert!(!registry.has_access(grantee.clone(), "creds".into()));
+    assert!(!registry.has_access(grantee.clone(), "wallets/1".into()));
+    assert_eq!(
+        registry
+            .grants_for(grantee.clone(), "creds/passport".into())
+            .unwrap()
+            .iter()
+            .map(|grant| grant.scope)
+            .collect::<Vec<_>>(),
+        vec![GrantScope::Prefix, GrantScope::Exact]
+    );
+
+    registry
+        .delete_grant(grantee.clone(), "creds/passport".into(), None)
+        .unwrap();
+    assert!(registry.has_access(grantee.clone(), "creds/passport".into()));
+
+    registry
+        .delete_prefix_grant(grantee.clone(), "creds/".into(), None)
+        .unwrap();
+    assert!(!registry.has_access(grantee.clone(), "creds/passport".into()));
+
+    assert_eq!(
+        registry.insert_prefix_grant(grantee, "".into(), None, None, None, None, None),
+        Err(RegistryError::EmptyDataIdPrefix)
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn find_grants_filters_by_purpose_and_access() {
+    let mut registry = FractalRegistry::default();
+    let owner = env::signer_account_pk();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let access = |mode| AccessScope {
+        mode,
+        fields: vec!["name".into()],
+    };
+
+    registry
+        .insert_grant(
+            grantee.clone(),
+            "A1".into(),
+            None,
+            Some("kyc".into()),
+            Some(access(AccessMode::ReadOnly)),
+            None,
+            None,
+        )
+        .unwrap();
+    registry
+        .insert_grant(
+            grantee.clone(),
+            "A2".into(),
+            None,
+            Some("audit".into()),
+            Some(access(AccessMode::ReadWrite)),
+            None,
+            None,
+        )
+        .unwrap();
+    registry
+        .insert_grant(grantee.clone(), "A3".into(), None, None, None, None, None)
+        .unwrap();
+
+    let data_ids = |purpose: Option<&str>, access_mode| {
+        registry
+            .find_grants(
+                Some(owner.clone()),
+                None,
+                None,
+                purpose.map(String::from),
+                access_mode,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|grant| grant.data_id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(data_ids(Some("kyc"), None), vec!["A1"]);
+    assert_eq!(
+        data_ids(None, Some(AccessMode::ReadWrite)),
+        vec!["A2", "A3"]
+    );
+    assert_eq!(
+        data_ids(None, Some(AccessMode::ReadOnly)),
+        vec!["A1", "A2", "A3"]
+    );
+
+    assert_eq!(
+        registry.insert_grant(
+            grantee.clone(),
+            "A4".into(),
+            None,
+            Some("kyc\nlockedUntil: 0".into()),
+            None,
+            None,
+            None
+        ),
+        Err(RegistryError::InvalidPurpose)
+    );
+    assert_eq!(
+        registry.insert_grant(
+            grantee,
+            "A4".into(),
+            None,
+            None,
+            Some(AccessScope {
+                mode: AccessMode::ReadOnly,
+                fields: vec!["name,dob".into()],
+            }),
+            None,
+            None
+        ),
+        Err(RegistryError::InvalidAccessScope)
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn verify_consent_rehashes_the_terms() {
+    let mut registry = FractalRegistry::default();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let terms = "We'll only use your passport to check your age.";
+
+    registry
+        .insert_grant(
+            grantee.clone(),
+            "A1".into(),
+            None,
+            None,
+            None,
+            Some(consent_hash(terms)),
+            None,
+        )
+        .unwrap();
+    registry
+        .insert_grant(grantee.clone(), "A2".into(), None, None, None, None, None)
+        .unwrap();
+
+    let grant_id = |data_id: &str| {
+        derive_grant_id(
+            &registry
+                .grants_for(grantee.clone(), data_id.into())
+                .unwrap()[0],
+        )
+    };
+
+    assert_eq!(
+        registry.verify_consent(grant_id("A1"), terms.into()),
+        Ok(true)
+    );
+    assert_eq!(
+        registry.verify_consent(grant_id("A1"), format!("{} And sell it.", terms)),
+        Ok(false)
+    );
+    assert_eq!(
+        registry.verify_consent(grant_id("A2"), terms.into()),
+        Ok(false)
+    );
+    assert_eq!(
+        registry.verify_consent("not a grant".into(), terms.into()),
+        Err(RegistryError::GrantNotFound)
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn grants_only_give_access_from_valid_from_on() {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    testing_env!(VMContextBuilder::new().block_timestamp(1_000).build());
+
+    let mut registry = FractalRegistry::default();
+    let owner = env::signer_account_pk();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+
+    registry
+        .insert_grant(
+            grantee.clone(),
+            "A1".into(),
+            None,
+            None,
+            None,
+            None,
+            Some(2_000),
+        )
+        .unwrap();
+
+    assert!(!registry.has_access(grantee.clone(), "A1".into()));
+    assert_eq!(
+        registry.grants_for(grantee.clone(), "A1".into()),
+        Ok(vec![])
+    );
+    assert_eq!(
+        registry
+            .find_grants(Some(owner), None, None, None, None)
+            .unwrap()[0]
+            .valid_from,
+        2_000
+    );
+
+    testing_env!(VMContextBuilder::new().block_timestamp(2_000).build());
+    assert!(registry.has_access(grantee.clone(), "A1".into()));
+    assert_eq!(registry.grants_for(grantee, "A1".into()).unwrap().len(), 1);
+}
@@ -0,0 +1,143 @@
+// Grantees' minimum timelocks. Inserting a grant that falls under a grantee's policy fails unless it's locked for at
+// least that long from the block it lands in.
+
+use near_sdk::{env, near_bindgen, Duration, EpochHeight, PublicKey};
+
+use crate::{
+    FractalRegistry, FractalRegistryEvents, FractalRegistryExt, GrantScope, LockPolicy,
+    RegistryError, MAX_LOCK_POLICIES,
+};
+
+impl FractalRegistry {
+    // The longest of `grantee`'s policies applying to the grant, or 0 when none does.
+    fn required_lock_duration(
+        &self,
+        grantee: &PublicKey,
+        data_id: &str,
+        scope: GrantScope,
+    ) -> Duration {
+        self.lock_policies(grantee.clone())
+            .iter()
+            .filter(|policy| policy.applies_to(data_id, scope))
+            .map(|policy| policy.min_lock_duration)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn check_lock_policies(
+        &self,
+        grantee: &PublicKey,
+        data_id: &str,
+        scope: GrantScope,
+        locked_until: EpochHeight,
+    ) -> Result<(), RegistryError> {
+        let required = self.required_lock_duration(grantee, data_id, scope);
+
+        if required > 0 && locked_until < env::block_timestamp().saturating_add(required) {
+            return Err(RegistryError::LockTooShort);
+        }
+
+        Ok(())
+    }
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    // Replaces all of the signer's policies. Grants already inserted keep their locks.
+    #[handle_result]
+    pub fn set_lock_policies(&mut self, policies: Vec<LockPolicy>) -> Result<(), RegistryError> {
+        if policies.len() > MAX_LOCK_POLICIES {
+            return Err(RegistryError::TooManyLockPolicies);
+        }
+
+        let grantee = env::signer_account_pk();
+
+        if policies.is_empty() {
+            self.lock_policies_by_grantee.remove(&grantee);
+        } else {
+            self.lock_policies_by_grantee
+                .insert(grantee.clone(), policies.clone());
+        }
+
+        FractalRegistryEvents::LockPoliciesSet { grantee, policies }.emit();
+
+        Ok(())
+    }
+
+    pub fn lock_policies(&self, grantee: PublicKey) -> Vec<LockPolicy> {
+        self.lock_policies_by_grantee
+            .get(&grantee)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // The earliest `locked_until` `grantee` accepts for the grant, as of this block, or 0 when any will do. Inserts
+    // land in later blocks, so wallets should add some slack before asking owners to sign.
+    pub fn min_locked_until(
+        &self,
+        grantee: PublicKey,
+        data_id: String,
+        scope: Option<GrantScope>,
+    ) -> EpochHeight {
+        match self.required_lock_duration(&grantee, &data_id, scope.unwrap_or_default()) {
+            0 => 0,
+            required => env::block_timestamp().saturating_add(required),
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn inserts_have_to_meet_the_grantees_lock_policies() {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let mut registry = FractalRegistry::default();
+
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(grantee.clone())
+        .block_timestamp(1_000)
+        .build());
+    registry
+        .set_lock_policies(vec![
+            LockPolicy {
+                data_id_prefix: "kyc/".into(),
+                min_lock_duration: 500,
+            },
+            LockPolicy {
+                data_id_prefix: "kyc/passport".into(),
+                min_lock_duration: 2_000,
+            },
+        ])
+        .unwrap();
+
+    testing_env!(VMContextBuilder::new().block_timestamp(1_000).build());
+    let mut insert = |data_id: &str, locked_until| {
+        registry.insert_grant(
+            grantee.clone(),
+            data_id.into(),
+            Some(locked_until),
+            None,
+            None,
+            None,
+            None,
+        )
+    };
+
+    assert_eq!(
+        insert("kyc/passport", 2_999),
+        Err(RegistryError::LockTooShort)
+    );
+    insert("kyc/passport", 3_000).unwrap();
+    insert("kyc/address", 1_500).unwrap();
+    insert("email", 0).unwrap();
+
+    assert_eq!(
+        registry.min_locked_until(grantee.clone(), "kyc/".into(), Some(GrantScope::Prefix)),
+        3_000
+    );
+    assert_eq!(registry.min_locked_until(grantee, "email".into(), None), 0);
+}
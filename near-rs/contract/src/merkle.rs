@@ -0,0 +1,356 @@
+// A sparse Merkle tree over `grants_by_id`, keyed by grant id.
+//
+// To keep it cheap, a subtree holding a single grant is stored as just that leaf, as high up the tree as it
+// can go (instead of going all the way down to depth 256). Internal nodes always have at least two leaves
+// under them. This keeps the tree shape dependent only on the set of grants, not on insertion order.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::near_bindgen;
+
+use access_grants_core::merkle::{
+    bit_at, grant_key, grant_value_hash, leaf_hash, node_hash, GrantProof, Hash, MerkleLeaf,
+    EMPTY_HASH,
+};
+
+use crate::{FractalRegistry, FractalRegistryExt, Grant, RegistryError};
+
+#[derive(BorshDeserialize, BorshSerialize, Clone)]
+pub enum MerkleNode {
+    Leaf { key: Hash, value_hash: Hash },
+    Internal { left: Hash, right: Hash },
+}
+
+fn with_bit_at(key: &Hash, depth: usize, bit: u8) -> Hash {
+    let mut result = *key;
+    let mask = 1 << (7 - depth % 8);
+
+    if bit == 0 {
+        result[depth / 8] &= !mask;
+    } else {
+        result[depth / 8] |= mask;
+    }
+
+    result
+}
+
+// Depth, followed by the first `depth` bits of the key (the rest zeroed out).
+fn node_position(key: &Hash, depth: usize) -> Vec<u8> {
+    let mut position = (depth as u16).to_be_bytes().to_vec();
+    let byte_count = (depth + 7) / 8;
+    position.extend_from_slice(&key[..byte_count]);
+
+    if depth % 8 != 0 {
+        position[2 + byte_count - 1] &= 0xff << (8 - depth % 8);
+    }
+
+    position
+}
+
+fn subtree_hash(node: Option<&MerkleNode>) -> Hash {
+    match node {
+        None => EMPTY_HASH,
+        Some(MerkleNode::Leaf { key, value_hash }) => leaf_hash(key, value_hash),
+        Some(MerkleNode::Internal { left, right }) => node_hash(left, right),
+    }
+}
+
+impl FractalRegistry {
+    fn merkle_node(&self, key: &Hash, depth: usize) -> Option<&MerkleNode> {
+        self.merkle_nodes.get(&node_position(key, depth))
+    }
+
+    // Recomputes (and, after a removal, collapses) every node on the way from `depth` back up to the root.
+    fn merkle_rehash_path(&mut self, key: &Hash, depth: usize) {
+        for depth in (0..depth).rev() {
+            let left_key = with_bit_at(key, depth, 0);
+            let right_key = with_bit_at(key, depth, 1);
+            let left = self.merkle_node(&left_key, depth + 1).cloned();
+            let right = self.merkle_node(&right_key, depth + 1).cloned();
+
+            let position = node_position(key, depth);
+
+            match (left, right) {
+                (None, None) => {
+                    self.merkle_nodes.remove(&position);
+                }
+                (Some(leaf @ MerkleNode::Leaf { .. }), None) => {
+                    self.merkle_nodes
+                        .remove(&node_position(&left_key, depth + 1));
+                    self.merkle_nodes.insert(position, leaf);
+                }
+                (None, Some(leaf @ MerkleNode::Leaf { .. })) => {
+                    self.merkle_nodes
+                        .remove(&node_position(&right_key, depth + 1));
+                    self.merkle_nodes.insert(position, leaf);
+                }
+                (left, right) => {
+                    self.merkle_nodes.insert(
+                        position,
+                        MerkleNode::Internal {
+                            left: subtree_hash(left.as_ref()),
+                            right: subtree_hash(right.as_ref()),
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn merkle_insert(&mut self, key: Hash, value_hash: Hash) {
+        let mut depth = 0;
+        while let Some(MerkleNode::Internal { .. }) = self.merkle_node(&key, depth) {
+            depth += 1;
+        }
+
+        let leaf = MerkleNode::Leaf { key, value_hash };
+
+        let leaf_depth = match self.merkle_node(&key, depth).cloned() {
+            Some(MerkleNode::Leaf {
+                key: other_key,
+                value_hash: other_value_hash,
+            }) if other_key != key => {
+                // Push the existing leaf down until the two keys diverge.
+                self.merkle_nodes.remove(&node_position(&key, depth));
+
+                let mut split_depth = depth;
+                while bit_at(&key, split_depth) == bit_at(&other_key, split_depth) {
+                    split_depth += 1;
+                }
+
+                self.merkle_nodes.insert(
+                    node_position(&other_key, split_depth + 1),
+                    MerkleNode::Leaf {
+                        key: other_key,
+                        value_hash: other_value_hash,
+                    },
+                );
+                self.merkle_nodes
+                    .insert(node_position(&key, split_depth + 1), leaf);
+
+                split_depth + 1
+            }
+            _ => {
+                self.merkle_nodes.insert(node_position(&key, depth), leaf);
+
+                depth
+            }
+        };
+
+        self.merkle_rehash_path(&key, leaf_depth);
+    }
+
+    fn merkle_remove(&mut self, key: Hash) {
+        let mut depth = 0;
+        loop {
+            match self.merkle_node(&key, depth) {
+                Some(MerkleNode::Internal { .. }) => depth += 1,
+                Some(MerkleNode::Leaf { key: leaf_key, .. }) if *leaf_key == key => break,
+                _ => return,
+            }
+        }
+
+        self.merkle_nodes.remove(&node_position(&key, depth));
+
+        self.merkle_rehash_path(&key, depth);
+    }
+
+    pub(crate) fn merkle_insert_grant(&mut self, grant_id: &str, grant: &Grant) {
+        let key = grant_key(grant_id).expect("Grant ids are always 32 bytes of hex");
+        self.merkle_insert(key, grant_value_hash(grant));
+    }
+
+    pub(crate) fn merkle_remove_grant(&mut self, grant_id: &str) {
+        let key = grant_key(grant_id).expect("Grant ids are always 32 bytes of hex");
+        self.merkle_remove(key);
+    }
+
+    fn merkle_proof(&self, key: &Hash) -> GrantProof {
+        let mut siblings = vec![];
+        let mut depth = 0;
+
+        loop {
+            match self.merkle_node(key, depth) {
+                Some(MerkleNode::Internal { left, right }) => {
+                    let sibling = match bit_at(key, depth) {
+                        0 => right,
+                        _ => left,
+                    };
+                    siblings.push(hex::encode(sibling));
+                    depth += 1;
+                }
+                Some(MerkleNode::Leaf { key, value_hash }) => {
+                    return GrantProof {
+                        leaf: Some(MerkleLeaf {
+                            key: hex::encode(key),
+                            value_hash: hex::encode(value_hash),
+                        }),
+                        siblings,
+                    }
+                }
+                None => {
+                    return GrantProof {
+                        leaf: None,
+                        siblings,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    pub fn grants_root(&self) -> String {
+        hex::encode(subtree_hash(
+            self.merkle_nodes.get(&node_position(&EMPTY_HASH, 0)),
+        ))
+    }
+
+    #[handle_result]
+    pub fn grant_proof(&self, grant_id: String) -> Result<GrantProof, RegistryError> {
+        let key = grant_key(&grant_id).ok_or(RegistryError::InvalidGrantId)?;
+
+        Ok(self.merkle_proof(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use access_grants_core::merkle::verify_grant_proof;
+
+    use super::*;
+
+    fn grant(data_id: &str) -> Grant {
+        Grant {
+            owner: "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+                .parse()
+                .unwrap(),
+            grantee: "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+                .parse()
+                .unwrap(),
+            data_id: data_id.into(),
+            scope: crate::GrantScope::Exact,
+            locked_until: 0,
+            purpose: None,
+            access: None,
+            consent_hash: None,
+            valid_from: 0,
+        }
+    }
+
+    fn insert(registry: &mut FractalRegistry, grant: &Grant) -> String {
+        let grant_id = crate::derive_grant_id(grant);
+        registry.merkle_insert_grant(&grant_id, grant);
+        grant_id
+    }
+
+    #[test]
+    fn empty_tree() {
+        let registry = FractalRegistry::default();
+        let root = registry.grants_root();
+        let grant = grant("A1");
+        let grant_id = crate::derive_grant_id(&grant);
+
+        assert_eq!(root, hex::encode(EMPTY_HASH));
+        assert!(verify_grant_proof(
+            &root,
+            &grant_id,
+            None,
+            &registry.grant_proof(grant_id.clone()).unwrap()
+        ));
+    }
+
+    #[test]
+    fn inclusion_and_exclusion() {
+        let mut registry = FractalRegistry::default();
+        let grants: Vec<Grant> = (0..20).map(|i| grant(&format!("A{}", i))).collect();
+        let grant_ids: Vec<String> = grants.iter().map(|g| insert(&mut registry, g)).collect();
+        let root = registry.grants_root();
+
+        for (grant, grant_id) in grants.iter().zip(&grant_ids) {
+            let proof = registry.grant_proof(grant_id.clone()).unwrap();
+            assert!(verify_grant_proof(&root, grant_id, Some(grant), &proof));
+            assert!(!verify_grant_proof(&root, grant_id, None, &proof));
+        }
+
+        let proof = registry.grant_proof(grant_ids[1].clone()).unwrap();
+        assert!(!verify_grant_proof(
+            &root,
+            &grant_ids[1],
+            Some(&grants[2]),
+            &proof
+        ));
+        assert!(!verify_grant_proof(
+            &root,
+            &grant_ids[2],
+            Some(&grants[2]),
+            &proof
+        ));
+
+        let missing = grant("B1");
+        let missing_id = crate::derive_grant_id(&missing);
+        let proof = registry.grant_proof(missing_id.clone()).unwrap();
+        assert!(verify_grant_proof(&root, &missing_id, None, &proof));
+        assert!(!verify_grant_proof(
+            &root,
+            &missing_id,
+            Some(&missing),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn root_only_depends_on_contents() {
+        let mut registry = FractalRegistry::default();
+        let a = grant("A");
+        let b = grant("B");
+        let c = grant("C");
+
+        insert(&mut registry, &a);
+        insert(&mut registry, &c);
+        let root_without_b = registry.grants_root();
+
+        let b_id = insert(&mut registry, &b);
+        assert_ne!(registry.grants_root(), root_without_b);
+
+        registry.merkle_remove_grant(&b_id);
+        assert_eq!(registry.grants_root(), root_without_b);
+
+        let mut other_registry = FractalRegistry::default();
+        insert(&mut other_registry, &c);
+        insert(&mut other_registry, &a);
+        assert_eq!(other_registry.grants_root(), root_without_b);
+    }
+
+    #[test]
+    fn keys_diverging_at_the_last_bit() {
+        let mut registry = FractalRegistry::default();
+        let key = [0xab; 32];
+        let other_key = with_bit_at(&key, 255, 1 - bit_at(&key, 255));
+
+        registry.merkle_insert(key, [1; 32]);
+        registry.merkle_insert(other_key, [2; 32]);
+
+        let root = registry.grants_root();
+        let proof = registry.merkle_proof(&key);
+        assert_eq!(proof.siblings.len(), 256);
+        assert_eq!(
+            proof.leaf,
+            Some(MerkleLeaf {
+                key: hex::encode(key),
+                value_hash: hex::encode([1; 32]),
+            })
+        );
+
+        registry.merkle_remove(other_key);
+        assert_eq!(registry.merkle_proof(&key).siblings.len(), 0);
+        assert_eq!(
+            registry.grants_root(),
+            hex::encode(leaf_hash(&key, &[1; 32]))
+        );
+        assert_ne!(registry.grants_root(), root);
+
+        registry.merkle_remove(key);
+        assert_eq!(registry.grants_root(), hex::encode(EMPTY_HASH));
+    }
+}
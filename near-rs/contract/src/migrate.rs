@@ -0,0 +1,329 @@
+// Moving STATE from older layouts to the current one. This code can't read an older layout at all, so deploying it
+// over one (like `idos-dev-4.near` from deploy.sh) has to call `migrate` in the same transaction:
+//
+//     near deploy <account> <wasm> --initFunction migrate --initArgs '{"legacy_grant_count": 1234}'
+//
+// The grants themselves move over afterwards, a batch per `migrate_grants` call, so no single call runs out of gas.
+// Lookup maps can't be iterated, so the old grant ids come from outside: the `grant_inserted` (and `grant_deleted`)
+// events the account emitted, say, as the indexer has them.
+//
+// Until they've all moved, the indexes still hold legacy ids whose grants nothing can read, so whatever reads grants
+// through them fails with MIGRATION_IN_PROGRESS.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::store::LookupMap;
+use near_sdk::{env, near_bindgen, EpochHeight, PublicKey};
+
+use crate::{
+    derive_grant_id, FractalRegistry, FractalRegistryExt, Grant, GrantScope, RegistryError,
+};
+
+// Grants before scopes, purposes, access scopes, consent hashes and start times.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct GrantV0 {
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub locked_until: EpochHeight,
+}
+
+impl From<GrantV0> for Grant {
+    fn from(grant: GrantV0) -> Self {
+        Self {
+            owner: grant.owner,
+            grantee: grant.grantee,
+            data_id: grant.data_id,
+            scope: GrantScope::Exact,
+            locked_until: grant.locked_until,
+            purpose: None,
+            access: None,
+            consent_hash: None,
+            valid_from: 0,
+        }
+    }
+}
+
+// The first layout: grants and their three indexes, and nothing else.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct FractalRegistryV0 {
+    pub grants_by_id: LookupMap<String, GrantV0>,
+    pub grant_ids_by_owner: LookupMap<PublicKey, Vec<String>>,
+    pub grant_ids_by_grantee: LookupMap<PublicKey, Vec<String>>,
+    pub grant_ids_by_data_id: LookupMap<String, Vec<String>>,
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    // Works out which older layout STATE is in (so far there's only V0) and switches to the current one.
+    // `legacy_grant_count` is how many grants the old layout stored: reads stay refused until that many have been
+    // through `migrate_grants`.
+    #[private]
+    #[init(ignore_state)]
+    #[handle_result]
+    pub fn migrate(legacy_grant_count: u32) -> Result<Self, RegistryError> {
+        let state = env::storage_read(b"STATE").ok_or(RegistryError::NothingToMigrate)?;
+
+        // Borsh wants every byte used, so the current layout never passes for an older one.
+        if FractalRegistryV0::try_from_slice(&state).is_ok() {
+            return Ok(Self {
+                legacy_grants_left: legacy_grant_count,
+                ..Self::default()
+            });
+        }
+
+        Err(RegistryError::NothingToMigrate)
+    }
+
+    // Moves the grants under `legacy_grant_ids` to the current layout, and returns how many are left. A batch with an
+    // id that isn't a legacy grant (or already moved) fails as a whole, so it can be fixed up and sent again.
+    #[private]
+    #[handle_result]
+    pub fn migrate_grants(&mut self, legacy_grant_ids: Vec<String>) -> Result<u32, RegistryError> {
+        if self.legacy_grants_left == 0 {
+            return Err(RegistryError::NothingToMigrate);
+        }
+
+        // V0's grants are still under its prefix, just in the old encoding.
+        let mut legacy_grants: LookupMap<String, GrantV0> = LookupMap::new(b"g");
+        let mut grants = Vec::with_capacity(legacy_grant_ids.len());
+
+        for grant_id in legacy_grant_ids {
+            let grant = legacy_grants
+                .remove(&grant_id)
+                .ok_or(RegistryError::GrantNotFound)?;
+            grants.push((grant_id, Grant::from(grant)));
+        }
+
+        // Both maps share the `g` prefix: the old entries have to be gone before the new ones get written.
+        legacy_grants.flush();
+
+        self.legacy_grants_left = self.legacy_grants_left.saturating_sub(grants.len() as u32);
+
+        for (legacy_grant_id, grant) in grants {
+            self.migrate_grant(&legacy_grant_id, grant);
+        }
+
+        Ok(self.legacy_grants_left)
+    }
+
+    pub fn legacy_grants_left(&self) -> u32 {
+        self.legacy_grants_left
+    }
+}
+
+impl FractalRegistry {
+    pub(crate) fn require_migrated(&self) -> Result<(), RegistryError> {
+        if self.legacy_grants_left > 0 {
+            return Err(RegistryError::MigrationInProgress);
+        }
+
+        Ok(())
+    }
+
+    // V0's collections kept their prefixes, so the defaults pick them up again. Everything added since starts out
+    // empty, except for the Merkle tree, which gets the re-encoded grants.
+    //
+    // V0 grant ids hashed in `locked_until` too, so grants move to their current ids. Ones that only differed by
+    // their lock become a single grant, keeping the longest lock.
+    fn migrate_grant(&mut self, legacy_grant_id: &str, grant: Grant) {
+        let grant_id = derive_grant_id(&grant);
+        let grant = match self.grants_by_id.get(&grant_id) {
+            Some(merged) if merged.locked_until >= grant.locked_until => merged.clone(),
+            _ => grant,
+        };
+
+        for ids in [
+            self.grant_ids_by_owner.entry(grant.owner.clone()),
+            self.grant_ids_by_grantee.entry(grant.grantee.clone()),
+        ] {
+            rekey(ids.or_default(), legacy_grant_id, &grant_id);
+        }
+        rekey(
+            self.grant_ids_by_data_id
+                .entry(grant.data_id.clone())
+                .or_default(),
+            legacy_grant_id,
+            &grant_id,
+        );
+
+        self.merkle_insert_grant(&grant_id, &grant);
+        self.grants_by_id.insert(grant_id, grant);
+    }
+}
+
+// Swaps `legacy_grant_id` for `grant_id` in an index, where it keeps its place. If `grant_id` is already there, from
+// a grant merged into it, the legacy id just goes.
+fn rekey(grant_ids: &mut Vec<String>, legacy_grant_id: &str, grant_id: &str) {
+    if grant_ids.iter().any(|id| id == grant_id) {
+        grant_ids.retain(|id| id != legacy_grant_id);
+    } else if let Some(id) = grant_ids.iter_mut().find(|id| *id == legacy_grant_id) {
+        *id = grant_id.into();
+    } else {
+        grant_ids.push(grant_id.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    use super::*;
+
+    // STATE as the first deployed version wrote it: the prefixes of its four lookup maps.
+    const V0_STATE: &[u8] = &[
+        1, 0, 0, 0, b'g', 1, 0, 0, 0, b'h', 1, 0, 0, 0, b'i', 1, 0, 0, 0, b'j',
+    ];
+
+    fn owner() -> PublicKey {
+        "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+            .parse()
+            .unwrap()
+    }
+
+    fn grantee() -> PublicKey {
+        "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+            .parse()
+            .unwrap()
+    }
+
+    // How the first version derived grant ids.
+    fn legacy_grant_id(grant: &GrantV0) -> String {
+        let id = format!(
+            "{}{}{}{}",
+            String::from(&grant.owner),
+            String::from(&grant.grantee),
+            grant.data_id,
+            grant.locked_until,
+        );
+
+        hex::encode(env::keccak256(id.as_bytes()))
+    }
+
+    // Writes `grants` the way the first version did, and returns their ids.
+    fn write_v0_state(grants: Vec<GrantV0>) -> Vec<String> {
+        env::storage_write(b"STATE", V0_STATE);
+
+        let mut v0 = FractalRegistryV0::try_from_slice(V0_STATE).unwrap();
+        let mut grant_ids = vec![];
+
+        for grant in grants {
+            let grant_id = legacy_grant_id(&grant);
+
+            for (index, key) in [
+                (&mut v0.grant_ids_by_owner, &grant.owner),
+                (&mut v0.grant_ids_by_grantee, &grant.grantee),
+            ] {
+                index.entry(key.clone()).or_default().push(grant_id.clone());
+            }
+            v0.grant_ids_by_data_id
+                .entry(grant.data_id.clone())
+                .or_default()
+                .push(grant_id.clone());
+            v0.grants_by_id.insert(grant_id.clone(), grant);
+
+            grant_ids.push(grant_id);
+        }
+
+        grant_ids
+    }
+
+    #[test]
+    fn migrates_the_first_layout() {
+        testing_env!(VMContextBuilder::new()
+            .signer_account_pk(owner())
+            .block_timestamp(1)
+            .build());
+
+        assert_eq!(
+            FractalRegistry::migrate(3).err(),
+            Some(RegistryError::NothingToMigrate)
+        );
+
+        let grant_ids = write_v0_state(vec![
+            GrantV0 {
+                owner: owner(),
+                grantee: grantee(),
+                data_id: "A1".into(),
+                locked_until: 0,
+            },
+            GrantV0 {
+                owner: owner(),
+                grantee: grantee(),
+                data_id: "A2".into(),
+                locked_until: 1337,
+            },
+            GrantV0 {
+                owner: owner(),
+                grantee: grantee(),
+                data_id: "A2".into(),
+                locked_until: 7,
+            },
+        ]);
+        let mut registry = FractalRegistry::migrate(3).unwrap();
+        assert_eq!(
+            registry.migrate_grants(vec!["not a grant".into()]),
+            Err(RegistryError::GrantNotFound)
+        );
+
+        // Unmigrated grants can't be read, so reads wait for the rest.
+        assert_eq!(registry.migrate_grants(grant_ids[..2].to_vec()), Ok(1));
+        assert_eq!(
+            registry.find_grants(Some(owner()), None, None, None, None),
+            Err(RegistryError::MigrationInProgress)
+        );
+        assert_eq!(
+            registry.revoke_all(None, None, None).err(),
+            Some(RegistryError::MigrationInProgress)
+        );
+        assert!(!registry.has_access(grantee(), "A1".into()));
+
+        assert_eq!(registry.migrate_grants(grant_ids[2..].to_vec()), Ok(0));
+        assert_eq!(
+            registry.migrate_grants(grant_ids.clone()),
+            Err(RegistryError::NothingToMigrate)
+        );
+
+        assert!(registry.has_access(grantee(), "A1".into()));
+        let grants = registry
+            .find_grants(Some(owner()), None, Some("A2".into()), None, None)
+            .unwrap();
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].scope, GrantScope::Exact);
+        assert_eq!(grants[0].locked_until, 1337);
+        assert_eq!(
+            registry.grant_ids_by_owner.get(&owner()).unwrap(),
+            &vec![derive_grant_id(&Grant::from(GrantV0 {
+                owner: owner(),
+                grantee: grantee(),
+                data_id: "A1".into(),
+                locked_until: 0,
+            }))]
+            .into_iter()
+            .chain([derive_grant_id(&grants[0])])
+            .collect::<Vec<_>>()
+        );
+        assert_ne!(registry.grants_root(), hex::encode([0; 32]));
+
+        // Migrated grants are under the ids everything else derives.
+        registry.delete_grant(grantee(), "A1".into(), None).unwrap();
+        assert!(!registry.has_access(grantee(), "A1".into()));
+        assert_eq!(
+            registry.delete_grant(grantee(), "A2".into(), None),
+            Err(RegistryError::GrantTimelocked)
+        );
+
+        registry
+            .insert_grant(grantee(), "A3".into(), None, None, None, None, None)
+            .unwrap();
+        registry.delete_grant(grantee(), "A3".into(), None).unwrap();
+        assert_eq!(registry.revocations_for(grantee(), None, None).len(), 2);
+        assert_eq!(registry.changes_since(0, None).latest_seq, 3);
+
+        env::storage_write(b"STATE", &borsh::to_vec(&registry).unwrap());
+        assert_eq!(
+            FractalRegistry::migrate(0).err(),
+            Some(RegistryError::NothingToMigrate)
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, NearSchema, PublicKey};
+
+use crate::{FractalRegistry, FractalRegistryExt, Grant, RegistryError};
+
+// The grants `find_grants` would return for one `grantee` and `data_id` pair. Either one is `None` when it wasn't
+// part of the query.
+#[derive(Serialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantsForKey {
+    pub grantee: Option<PublicKey>,
+    pub data_id: Option<String>,
+    pub grants: Vec<Grant>,
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    // Like calling `find_grants` for every combination of `grantees` and `data_ids`, but each index is read once.
+    // An empty list leaves that filter out, as a `None` would in `find_grants`.
+    //
+    // Like `find_grants`, it lists grants that start later too. Access checks want `active_only`, which keeps just
+    // the ones `grants_for` would return.
+    #[handle_result]
+    pub fn find_grants_multi(
+        &self,
+        owner: Option<PublicKey>,
+        grantees: Vec<PublicKey>,
+        data_ids: Vec<String>,
+        active_only: Option<bool>,
+    ) -> Result<Vec<GrantsForKey>, RegistryError> {
+        if owner.is_none() && grantees.is_empty() {
+            return Err(RegistryError::MissingOwnerOrGrantee);
+        }
+
+        let empty = vec![];
+        let owner_grant_ids =
+            owner.map(|owner| self.grant_ids_by_owner.get(&owner).unwrap_or(&empty));
+
+        let grantee_grant_ids: Vec<_> = if grantees.is_empty() {
+            vec![(None, None)]
+        } else {
+            grantees
+                .into_iter()
+                .map(|grantee| {
+                    let grant_ids = self.grant_ids_by_grantee.get(&grantee).unwrap_or(&empty);
+                    (Some(grantee), Some(grant_ids))
+                })
+                .collect()
+        };
+
+        let data_id_grant_ids: Vec<_> = if data_ids.is_empty() {
+            vec![(None, None)]
+        } else {
+            data_ids
+                .into_iter()
+                .map(|data_id| {
+                    let grant_ids = self.grant_ids_covering(&data_id);
+                    (Some(data_id), Some(grant_ids))
+                })
+                .collect()
+        };
+
+        let mut results = Vec::with_capacity(grantee_grant_ids.len() * data_id_grant_ids.len());
+
+        for (grantee, grantee_ids) in &grantee_grant_ids {
+            for (data_id, data_id_ids) in &data_id_grant_ids {
+                let grant_id_searches: Vec<&Vec<String>> =
+                    IntoIterator::into_iter([owner_grant_ids, *grantee_ids, data_id_ids.as_ref()])
+                        .flatten()
+                        .collect();
+
+                let mut grants = self.grants_in_all(&grant_id_searches)?;
+                if active_only == Some(true) {
+                    grants.retain(|grant| grant.is_active(env::block_timestamp()));
+                }
+
+                results.push(GrantsForKey {
+                    grantee: grantee.clone(),
+                    data_id: data_id.clone(),
+                    grants,
+                });
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn find_grants_multi_matches_find_grants() {
+    let mut registry = FractalRegistry::default();
+    let bob: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let charlie: PublicKey = "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+        .parse()
+        .unwrap();
+
+    registry
+        .insert_grant(bob.clone(), "A1".into(), None, None, None, None, None)
+        .unwrap();
+    registry
+        .insert_grant(bob.clone(), "A2".into(), None, None, None, None, None)
+        .unwrap();
+    registry
+        .insert_prefix_grant(charlie.clone(), "A".into(), None, None, None, None, None)
+        .unwrap();
+
+    let results = registry
+        .find_grants_multi(
+            None,
+            vec![bob.clone(), charlie.clone()],
+            vec!["A1".into(), "A3".into()],
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(results.len(), 4);
+    for result in &results {
+        assert_eq!(
+            result.grants,
+            registry
+                .find_grants(
+                    None,
+                    result.grantee.clone(),
+                    result.data_id.clone(),
+                    None,
+                    None
+                )
+                .unwrap()
+        );
+    }
+    assert_eq!(
+        results
+            .iter()
+            .map(|result| result.grants.len())
+            .collect::<Vec<_>>(),
+        vec![1, 0, 1, 1]
+    );
+
+    registry
+        .insert_grant(
+            bob.clone(),
+            "A3".into(),
+            None,
+            None,
+            None,
+            None,
+            Some(u64::MAX),
+        )
+        .unwrap();
+    let grant_counts = |active_only| {
+        registry
+            .find_grants_multi(None, vec![bob.clone()], vec!["A3".into()], active_only)
+            .unwrap()[0]
+            .grants
+            .len()
+    };
+    assert_eq!(grant_counts(None), 1);
+    assert_eq!(grant_counts(Some(true)), 0);
+
+    assert_eq!(
+        registry.find_grants_multi(None, vec![], vec!["A1".into()], None),
+        Err(RegistryError::MissingOwnerOrGrantee)
+    );
+}
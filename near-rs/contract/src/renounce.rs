@@ -0,0 +1,185 @@
+use near_sdk::{env, near_bindgen, PublicKey};
+
+use crate::{
+    nep413_verify, renounce_grant_message, DeletionReason, Ed25519Signature, FractalRegistry,
+    FractalRegistryEvents, FractalRegistryExt, Nonce, RegistryError,
+};
+
+#[near_bindgen]
+impl FractalRegistry {
+    // The grantee giving up its grants for `data_id` from `owner`. Timelocks don't apply: they're there to protect
+    // the grantee in the first place.
+    #[handle_result]
+    pub fn renounce_grant(
+        &mut self,
+        owner: PublicKey,
+        data_id: String,
+    ) -> Result<(), RegistryError> {
+        let grantee = env::signer_account_pk();
+        let revision = self.renounce_revision(owner.clone(), grantee.clone(), data_id.clone()) + 1;
+
+        self._renounce_grant(owner, grantee, data_id, revision)
+    }
+
+    pub fn renounce_grant_by_signature_message(
+        &self,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id: String,
+        revision: u32,
+    ) -> String {
+        renounce_grant_message(&owner, &grantee, &data_id, revision)
+    }
+
+    // `revision` has to be one more than `renounce_revision(owner, grantee, data_id)`.
+    #[handle_result]
+    pub fn renounce_grant_by_signature(
+        &mut self,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id: String,
+        revision: u32,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<(), RegistryError> {
+        if self
+            .renounce_revision(owner.clone(), grantee.clone(), data_id.clone())
+            .checked_add(1)
+            != Some(revision)
+        {
+            return Err(RegistryError::InvalidRenounceRevision);
+        }
+
+        nep413_verify(
+            renounce_grant_message(&owner, &grantee, &data_id, revision),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &grantee,
+        )?;
+
+        self._renounce_grant(owner, grantee, data_id, revision)
+    }
+
+    // How many times `grantee` renounced `owner`'s grants for `data_id`. Both direct and signed renounces count, and
+    // the count outlives the grants, for when they're given again.
+    pub fn renounce_revision(&self, owner: PublicKey, grantee: PublicKey, data_id: String) -> u32 {
+        self.grant_renounce_revisions
+            .get(&(owner, grantee, data_id))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    // Exact and prefix grants alike, as long as their `data_id` is the one given.
+    fn _renounce_grant(
+        &mut self,
+        owner: PublicKey,
+        grantee: PublicKey,
+        data_id: String,
+        revision: u32,
+    ) -> Result<(), RegistryError> {
+        self.grant_renounce_revisions
+            .insert((owner.clone(), grantee.clone(), data_id.clone()), revision);
+
+        let grants = self.find_grants(
+            Some(owner),
+            Some(grantee.clone()),
+            Some(data_id.clone()),
+            None,
+            None,
+        )?;
+
+        for grant in grants.into_iter().filter(|grant| grant.data_id == data_id) {
+            self._remove_grant(&grant, grantee.clone(), DeletionReason::Renounced);
+
+            FractalRegistryEvents::GrantRenounced {
+                owner: grant.owner,
+                grantee: grant.grantee,
+                data_id: grant.data_id,
+                scope: grant.scope,
+                locked_until: grant.locked_until,
+            }
+            .emit();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn grantee_renounces_timelocked_grant() {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    let owner: PublicKey = "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+        .parse()
+        .unwrap();
+    let grantee: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let mut registry = FractalRegistry::default();
+
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(owner.clone())
+        .build());
+    registry
+        .insert_grant(
+            grantee.clone(),
+            "A1".into(),
+            Some(u64::MAX),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    registry
+        .insert_grant(
+            grantee.clone(),
+            "A2".into(),
+            Some(u64::MAX),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(grantee.clone())
+        .build());
+    registry.renounce_grant(owner.clone(), "A1".into()).unwrap();
+
+    assert_eq!(
+        registry
+            .find_grants(Some(owner.clone()), None, None, None, None)
+            .unwrap()
+            .iter()
+            .map(|grant| grant.data_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["A2"]
+    );
+
+    let tombstones = registry.revocations_for(grantee.clone(), None, None);
+    assert_eq!(tombstones.len(), 1);
+    assert_eq!(tombstones[0].deleted_by, grantee);
+    assert_eq!(tombstones[0].reason, DeletionReason::Renounced);
+
+    // A grant given again under the same id can't be renounced with a signature for the old one.
+    assert_eq!(
+        registry.renounce_revision(owner.clone(), grantee.clone(), "A1".into()),
+        1
+    );
+    assert_eq!(
+        registry.renounce_grant_by_signature(
+            owner,
+            grantee,
+            "A1".into(),
+            1,
+            Nonce([0; 32]),
+            Ed25519Signature([0; 64]),
+        ),
+        Err(RegistryError::InvalidRenounceRevision)
+    );
+}
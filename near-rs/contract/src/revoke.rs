@@ -0,0 +1,348 @@
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::serde::Serialize;
+use near_sdk::{env, near_bindgen, NearSchema, PublicKey};
+
+use crate::{
+    nep413_verify, revoke_all_message, DeletionReason, Ed25519Signature, FractalRegistry,
+    FractalRegistryEvents, FractalRegistryExt, Grant, Nonce, RegistryError,
+};
+
+// Grant removals can get expensive (indexes, Merkle tree, change log, tombstones), so each call only does this
+// many unless told otherwise.
+const DEFAULT_REVOKE_LIMIT: u32 = 20;
+
+// Grants kept (timelocked, or for other grantees) still cost a read each, so a call also stops after looking at
+// this many grants, revoked or not.
+const MAX_REVOKE_ALL_GRANTS_WALKED: usize = 100;
+
+// `next` is set when this call stopped before the end of the owner's grants: it's the id of the grant to look at
+// next, and passing it back as `from` picks up there. `timelocked` lists the grants this call went past that have
+// to stay for now.
+#[derive(Serialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RevokeAllResult {
+    pub revoked: u32,
+    pub timelocked: Vec<Grant>,
+    pub next: Option<String>,
+}
+
+// An owner's latest signed revoke-all. Its signature keeps working while there's a `next` to carry on from, which is
+// kept here rather than taken from whoever submits it.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SignedRevokeAll {
+    pub revision: u32,
+    pub next: Option<String>,
+}
+
+#[near_bindgen]
+impl FractalRegistry {
+    // `from` is the `next` of the call before, if this one carries on with it.
+    #[handle_result]
+    pub fn revoke_all(
+        &mut self,
+        grantee: Option<PublicKey>,
+        limit: Option<u32>,
+        from: Option<String>,
+    ) -> Result<RevokeAllResult, RegistryError> {
+        self._revoke_all(env::signer_account_pk(), grantee, limit, from)
+    }
+
+    pub fn revoke_all_by_signature_message(
+        &self,
+        owner: PublicKey,
+        grantee: Option<PublicKey>,
+        revision: u32,
+    ) -> String {
+        revoke_all_message(&owner, grantee.as_ref(), revision)
+    }
+
+    // `revision` has to be one more than `revoke_all_revision(owner)` to start over, or that one to carry on with it
+    // while it has a `next`. `limit` isn't part of the signed message, so the same signature can be resubmitted.
+    #[handle_result]
+    pub fn revoke_all_by_signature(
+        &mut self,
+        owner: PublicKey,
+        grantee: Option<PublicKey>,
+        revision: u32,
+        limit: Option<u32>,
+        nonce: Nonce,
+        signature: Ed25519Signature,
+    ) -> Result<RevokeAllResult, RegistryError> {
+        let from = match self.signed_revoke_alls.get(&owner) {
+            Some(latest) if latest.revision == revision && latest.next.is_some() => {
+                latest.next.clone()
+            }
+            _ if self.revoke_all_revision(owner.clone()).checked_add(1) == Some(revision) => None,
+            _ => return Err(RegistryError::InvalidRevokeAllRevision),
+        };
+
+        nep413_verify(
+            self.revoke_all_by_signature_message(owner.clone(), grantee.clone(), revision),
+            nonce,
+            self.grant_message_recipient(),
+            signature,
+            &owner,
+        )?;
+
+        let result = self._revoke_all(owner.clone(), grantee, limit, from)?;
+        self.signed_revoke_alls.insert(
+            owner,
+            SignedRevokeAll {
+                revision,
+                next: result.next.clone(),
+            },
+        );
+
+        Ok(result)
+    }
+
+    // How many signed revoke-alls `owner` started.
+    pub fn revoke_all_revision(&self, owner: PublicKey) -> u32 {
+        self.signed_revoke_alls
+            .get(&owner)
+            .map_or(0, |latest| latest.revision)
+    }
+
+    // `from` is the id of the grant to carry on at. Grants removed from the owner's index in between (renounced,
+    // deleted, revoked by another call) don't move it, and if `from` itself is gone this starts over: grants kept
+    // before get looked at again, but none get skipped.
+    fn _revoke_all(
+        &mut self,
+        owner: PublicKey,
+        grantee: Option<PublicKey>,
+        limit: Option<u32>,
+        from: Option<String>,
+    ) -> Result<RevokeAllResult, RegistryError> {
+        self.require_migrated()?;
+
+        let limit = limit.unwrap_or(DEFAULT_REVOKE_LIMIT);
+        let grant_ids = self
+            .grant_ids_by_owner
+            .get(&owner)
+            .cloned()
+            .unwrap_or_default();
+        let start = from
+            .and_then(|from| grant_ids.iter().position(|grant_id| *grant_id == from))
+            .unwrap_or(0);
+
+        let mut result = RevokeAllResult {
+            revoked: 0,
+            timelocked: vec![],
+            next: None,
+        };
+        for (walked, grant_id) in grant_ids[start..].iter().enumerate() {
+            if result.revoked == limit || walked == MAX_REVOKE_ALL_GRANTS_WALKED {
+                result.next = Some(grant_id.clone());
+                break;
+            }
+            let grant = self.grants_by_id.get(grant_id).unwrap().clone();
+
+            if grantee
+                .as_ref()
+                .is_some_and(|grantee| *grantee != grant.grantee)
+            {
+                continue;
+            }
+
+            if grant.locked_until >= env::block_timestamp() {
+                result.timelocked.push(grant);
+                continue;
+            }
+
+            self._remove_grant(&grant, owner.clone(), DeletionReason::RevokedAll);
+            result.revoked += 1;
+
+            FractalRegistryEvents::GrantDeleted {
+                owner: grant.owner,
+                grantee: grant.grantee,
+                data_id: grant.data_id,
+                scope: grant.scope,
+                locked_until: grant.locked_until,
+            }
+            .emit();
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn revoke_all_in_chunks_skipping_timelocked_grants() {
+    near_sdk::testing_env!(near_sdk::test_utils::VMContextBuilder::new()
+        .block_timestamp(10)
+        .build());
+
+    let mut registry = FractalRegistry::default();
+    let owner = env::signer_account_pk();
+    let bob: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    let charlie: PublicKey = "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+        .parse()
+        .unwrap();
+
+    for data_id in ["A1", "A2", "A3"] {
+        registry
+            .insert_grant(bob.clone(), data_id.into(), None, None, None, None, None)
+            .unwrap();
+    }
+    registry
+        .insert_grant(
+            bob.clone(),
+            "A4".into(),
+            Some(u64::MAX),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+    registry
+        .insert_grant(charlie.clone(), "A1".into(), None, None, None, None, None)
+        .unwrap();
+
+    let result = registry
+        .revoke_all(Some(bob.clone()), Some(2), None)
+        .unwrap();
+    assert_eq!(result.revoked, 2);
+    assert_eq!(
+        result.next.as_ref(),
+        registry.grant_ids_by_owner.get(&owner).unwrap().first()
+    );
+    assert_eq!(result.timelocked, vec![]);
+
+    let result = registry
+        .revoke_all(Some(bob.clone()), Some(2), result.next)
+        .unwrap();
+    assert_eq!(result.revoked, 1);
+    assert_eq!(result.next, None);
+    assert_eq!(
+        result
+            .timelocked
+            .iter()
+            .map(|grant| grant.data_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["A4"]
+    );
+
+    assert_eq!(
+        registry
+            .find_grants(Some(owner), None, None, None, None)
+            .unwrap()
+            .iter()
+            .map(|grant| grant.data_id.as_str())
+            .collect::<Vec<_>>(),
+        vec!["A4", "A1"]
+    );
+    assert!(registry
+        .grant_history(env::signer_account_pk(), None, None)
+        .iter()
+        .all(|tombstone| tombstone.reason == DeletionReason::RevokedAll));
+}
+
+#[cfg(test)]
+#[test]
+fn revoke_all_carries_on_past_grants_removed_in_between() {
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    let owner: PublicKey = "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+        .parse()
+        .unwrap();
+    let bob: PublicKey = "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+        .parse()
+        .unwrap();
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(owner.clone())
+        .block_timestamp(10)
+        .build());
+
+    let mut registry = FractalRegistry::default();
+    for (data_id, locked_until) in [("A1", Some(u64::MAX)), ("A2", None), ("A3", None)] {
+        registry
+            .insert_grant(
+                bob.clone(),
+                data_id.into(),
+                locked_until,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+    }
+
+    let result = registry
+        .revoke_all(Some(bob.clone()), Some(1), None)
+        .unwrap();
+    assert_eq!(result.revoked, 1);
+    assert_eq!(result.timelocked.len(), 1);
+    assert!(result.next.is_some());
+
+    // Bob gives up the timelocked grant, so the grants after it move up in the owner's index.
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(bob.clone())
+        .block_timestamp(10)
+        .build());
+    registry.renounce_grant(owner.clone(), "A1".into()).unwrap();
+    testing_env!(VMContextBuilder::new()
+        .signer_account_pk(owner.clone())
+        .block_timestamp(10)
+        .build());
+
+    let result = registry
+        .revoke_all(Some(bob), Some(1), result.next)
+        .unwrap();
+    assert_eq!(result.revoked, 1);
+    assert_eq!(result.next, None);
+    assert_eq!(
+        registry
+            .find_grants(Some(owner), None, None, None, None)
+            .unwrap(),
+        vec![]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn signed_revoke_alls_need_the_next_revision() {
+    let mut registry = FractalRegistry::default();
+    let owner: PublicKey = "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+        .parse()
+        .unwrap();
+    let revoke = |registry: &mut FractalRegistry, revision| {
+        registry.revoke_all_by_signature(
+            owner.clone(),
+            None,
+            revision,
+            None,
+            Nonce([0; 32]),
+            Ed25519Signature([0; 64]),
+        )
+    };
+
+    assert_eq!(
+        revoke(&mut registry, 2),
+        Err(RegistryError::InvalidRevokeAllRevision)
+    );
+    assert_eq!(
+        revoke(&mut registry, 1),
+        Err(RegistryError::InvalidSignature)
+    );
+
+    // A finished one can't be replayed, and an unfinished one can be carried on with.
+    for (next, result) in [
+        (None, Err(RegistryError::InvalidRevokeAllRevision)),
+        (Some("A3".into()), Err(RegistryError::InvalidSignature)),
+    ] {
+        registry
+            .signed_revoke_alls
+            .insert(owner.clone(), SignedRevokeAll { revision: 1, next });
+        assert_eq!(revoke(&mut registry, 1), result);
+    }
+    assert_eq!(
+        revoke(&mut registry, 2),
+        Err(RegistryError::InvalidSignature)
+    );
+}
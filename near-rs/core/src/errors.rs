@@ -0,0 +1,235 @@
+use std::fmt;
+
+use near_sdk::FunctionError;
+
+// Every way a `FractalRegistry` call fails on purpose. The contract panics with `<CODE>: <message>`. Codes are
+// stable and meant for branching on; messages are for humans and may change.
+#[derive(FunctionError, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RegistryError {
+    MissingOwnerOrGrantee,
+    GrantAlreadyExists,
+    GrantTimelocked,
+    UnsupportedKeyType,
+    InvalidPublicKey,
+    InvalidSignature,
+    InvalidGrantId,
+    EmptyDataIdPrefix,
+    AccessRequestNotFound,
+    AccessRequestExpired,
+    TooManyAccessRequests,
+    NotOwner,
+    NotContractAccount,
+    InvalidPurpose,
+    InvalidAccessScope,
+    GrantNotFound,
+    GrantKeyTooLarge,
+    InvalidRotation,
+    InvalidGranteeProfile,
+    GranteeNotRegistered,
+    GranteeDenied,
+    GranteeNotAllowed,
+    GranteeListFull,
+    InvalidListRevision,
+    TooManyLockPolicies,
+    LockTooShort,
+    LockNotExtended,
+    NothingToMigrate,
+    InvalidRevokeAllRevision,
+    InvalidGrantKeyRevision,
+    InvalidLockRevision,
+    InvalidRenounceRevision,
+    OwnerAccessRequestsFull,
+    MigrationInProgress,
+}
+
+impl RegistryError {
+    pub const ALL: &'static [RegistryError] = &[
+        Self::MissingOwnerOrGrantee,
+        Self::GrantAlreadyExists,
+        Self::GrantTimelocked,
+        Self::UnsupportedKeyType,
+        Self::InvalidPublicKey,
+        Self::InvalidSignature,
+        Self::InvalidGrantId,
+        Self::EmptyDataIdPrefix,
+        Self::AccessRequestNotFound,
+        Self::AccessRequestExpired,
+        Self::TooManyAccessRequests,
+        Self::NotOwner,
+        Self::NotContractAccount,
+        Self::InvalidPurpose,
+        Self::InvalidAccessScope,
+        Self::GrantNotFound,
+        Self::GrantKeyTooLarge,
+        Self::InvalidRotation,
+        Self::InvalidGranteeProfile,
+        Self::GranteeNotRegistered,
+        Self::GranteeDenied,
+        Self::GranteeNotAllowed,
+        Self::GranteeListFull,
+        Self::InvalidListRevision,
+        Self::TooManyLockPolicies,
+        Self::LockTooShort,
+        Self::LockNotExtended,
+        Self::NothingToMigrate,
+        Self::InvalidRevokeAllRevision,
+        Self::InvalidGrantKeyRevision,
+        Self::InvalidLockRevision,
+        Self::InvalidRenounceRevision,
+        Self::OwnerAccessRequestsFull,
+        Self::MigrationInProgress,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MissingOwnerOrGrantee => "MISSING_OWNER_OR_GRANTEE",
+            Self::GrantAlreadyExists => "GRANT_ALREADY_EXISTS",
+            Self::GrantTimelocked => "GRANT_TIMELOCKED",
+            Self::UnsupportedKeyType => "UNSUPPORTED_KEY_TYPE",
+            Self::InvalidPublicKey => "INVALID_PUBLIC_KEY",
+            Self::InvalidSignature => "INVALID_SIGNATURE",
+            Self::InvalidGrantId => "INVALID_GRANT_ID",
+            Self::EmptyDataIdPrefix => "EMPTY_DATA_ID_PREFIX",
+            Self::AccessRequestNotFound => "ACCESS_REQUEST_NOT_FOUND",
+            Self::AccessRequestExpired => "ACCESS_REQUEST_EXPIRED",
+            Self::TooManyAccessRequests => "TOO_MANY_ACCESS_REQUESTS",
+            Self::NotOwner => "NOT_OWNER",
+            Self::NotContractAccount => "NOT_CONTRACT_ACCOUNT",
+            Self::InvalidPurpose => "INVALID_PURPOSE",
+            Self::InvalidAccessScope => "INVALID_ACCESS_SCOPE",
+            Self::GrantNotFound => "GRANT_NOT_FOUND",
+            Self::GrantKeyTooLarge => "GRANT_KEY_TOO_LARGE",
+            Self::InvalidRotation => "INVALID_ROTATION",
+            Self::InvalidGranteeProfile => "INVALID_GRANTEE_PROFILE",
+            Self::GranteeNotRegistered => "GRANTEE_NOT_REGISTERED",
+            Self::GranteeDenied => "GRANTEE_DENIED",
+            Self::GranteeNotAllowed => "GRANTEE_NOT_ALLOWED",
+            Self::GranteeListFull => "GRANTEE_LIST_FULL",
+            Self::InvalidListRevision => "INVALID_LIST_REVISION",
+            Self::TooManyLockPolicies => "TOO_MANY_LOCK_POLICIES",
+            Self::LockTooShort => "LOCK_TOO_SHORT",
+            Self::LockNotExtended => "LOCK_NOT_EXTENDED",
+            Self::NothingToMigrate => "NOTHING_TO_MIGRATE",
+            Self::InvalidRevokeAllRevision => "INVALID_REVOKE_ALL_REVISION",
+            Self::InvalidGrantKeyRevision => "INVALID_GRANT_KEY_REVISION",
+            Self::InvalidLockRevision => "INVALID_LOCK_REVISION",
+            Self::InvalidRenounceRevision => "INVALID_RENOUNCE_REVISION",
+            Self::OwnerAccessRequestsFull => "OWNER_ACCESS_REQUESTS_FULL",
+            Self::MigrationInProgress => "MIGRATION_IN_PROGRESS",
+        }
+    }
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::MissingOwnerOrGrantee => "Required argument: `owner` and/or `grantee`",
+            Self::GrantAlreadyExists => "Grant already exists",
+            Self::GrantTimelocked => "Grant is timelocked",
+            Self::UnsupportedKeyType => "Only ed25519 keys are supported",
+            Self::InvalidPublicKey => "Public key doesn't have 32 bytes",
+            Self::InvalidSignature => "Signature doesn't match",
+            Self::InvalidGrantId => "Invalid grant id",
+            Self::EmptyDataIdPrefix => "`data_id_prefix` can't be empty",
+            Self::AccessRequestNotFound => "Access request not found",
+            Self::AccessRequestExpired => "Access request has expired",
+            Self::TooManyAccessRequests => {
+                "Grantee has too many pending access requests to this owner"
+            }
+            Self::NotOwner => "Only the owner can do this",
+            Self::NotContractAccount => "Only the contract account can do this",
+            Self::InvalidPurpose => {
+                "Purpose must have 1 to 64 bytes and no control characters"
+            }
+            Self::InvalidAccessScope => {
+                "Access fields must have 1 to 64 bytes, no commas or control characters, and be 32 at most"
+            }
+            Self::GrantNotFound => "Grant not found",
+            Self::GrantKeyTooLarge => "Encrypted key can't have more than 1024 bytes",
+            Self::InvalidRotation => "`rotation` must be one more than the grantee's latest",
+            Self::InvalidGranteeProfile => {
+                "Profile name must have 1 to 64 bytes and no control characters, and its URL be https:// and 256 bytes at most"
+            }
+            Self::GranteeNotRegistered => "Grantee has no registered profile",
+            Self::GranteeDenied => "Owner denies grants to this grantee",
+            Self::GranteeNotAllowed => "Grantee isn't on the owner's allow list",
+            Self::GranteeListFull => "Grantee lists can't have more than 100 grantees",
+            Self::InvalidListRevision => {
+                "`revision` must be one more than the owner's latest grantee list update"
+            }
+            Self::TooManyLockPolicies => "Grantees can't have more than 32 lock policies",
+            Self::LockTooShort => "`locked_until` is sooner than the grantee's lock policy requires",
+            Self::LockNotExtended => "`new_locked_until` must be later than the grant's current lock",
+            Self::NothingToMigrate => "Contract state isn't in a layout `migrate` knows",
+            Self::InvalidRevokeAllRevision => {
+                "`revision` must be one more than the owner's latest signed revoke-all, or that one if it's unfinished"
+            }
+            Self::InvalidGrantKeyRevision => {
+                "`revision` must be one more than the grant key's latest"
+            }
+            Self::InvalidLockRevision => {
+                "`revision` must be one more than the grant's latest lock extension"
+            }
+            Self::InvalidRenounceRevision => {
+                "`revision` must be one more than the grantee's latest renounce of these grants"
+            }
+            Self::OwnerAccessRequestsFull => "Owner has too many pending access requests",
+            Self::MigrationInProgress => "Grants are still being migrated",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|error| error.code() == code)
+    }
+
+    // Picks the error out of whatever a failed call turned into. Transactions say
+    // `Smart contract panicked: GRANT_TIMELOCKED: ...` while views say `panic_msg: "GRANT_TIMELOCKED: ..."`, so we
+    // look for any known code right before a `: `.
+    pub fn find_in(text: &str) -> Option<Self> {
+        text.match_indices(": ").find_map(|(end, _)| {
+            let start = text[..end]
+                .rfind(|c: char| !(c.is_ascii_uppercase() || c == '_'))
+                .map_or(0, |i| i + 1);
+
+            Self::from_code(&text[start..end])
+        })
+    }
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message())
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_unique_and_round_trip() {
+        for error in RegistryError::ALL {
+            assert_eq!(RegistryError::from_code(error.code()), Some(*error));
+        }
+    }
+
+    #[test]
+    fn find_in_call_and_view_errors() {
+        assert_eq!(
+            RegistryError::find_in(
+                r#"Action #0: ExecutionError("Smart contract panicked: GRANT_TIMELOCKED: Grant is timelocked")"#
+            ),
+            Some(RegistryError::GrantTimelocked)
+        );
+        assert_eq!(
+            RegistryError::find_in(
+                r#"wasm execution failed with error: HostError(GuestPanic { panic_msg: "INVALID_GRANT_ID: Invalid grant id" })"#
+            ),
+            Some(RegistryError::InvalidGrantId)
+        );
+        assert_eq!(
+            RegistryError::find_in("Smart contract panicked: something else: entirely"),
+            None
+        );
+    }
+}
@@ -0,0 +1,573 @@
+// Typed decoding of the `EVENT_JSON:` logs the contract emits through `FractalRegistryEvents`.
+//
+// Every version of every event we ever emitted stays decodable here, so indexers can replay old blocks.
+
+use std::fmt;
+
+use near_sdk::serde::Deserialize;
+use near_sdk::serde_json::{self, Value};
+use near_sdk::{EpochHeight, NearSchema, PublicKey, Timestamp};
+
+use crate::{
+    AccessScope, ConsentHash, ContentHash, EncryptionKey, GrantScope, GranteeList, LockPolicy,
+};
+
+pub const EVENT_JSON_PREFIX: &str = "EVENT_JSON";
+pub const EVENT_JSON_SEPARATOR: &str = ":";
+pub const EVENT_STANDARD: &str = "FractalRegistry";
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantEventV0 {
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub locked_until: EpochHeight,
+}
+
+// Version 1 added `scope`. Version 0 events are all for exact grants.
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantEventV1 {
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub scope: GrantScope,
+    pub locked_until: EpochHeight,
+}
+
+impl From<GrantEventV0> for GrantEventV1 {
+    fn from(event: GrantEventV0) -> Self {
+        Self {
+            owner: event.owner,
+            grantee: event.grantee,
+            data_id: event.data_id,
+            scope: GrantScope::Exact,
+            locked_until: event.locked_until,
+        }
+    }
+}
+
+// Version 2 of `grant_inserted` added `purpose` and `access`. Deletions don't need them, so they stay at 1.
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantEventV2 {
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub scope: GrantScope,
+    pub locked_until: EpochHeight,
+    pub purpose: Option<String>,
+    pub access: Option<AccessScope>,
+}
+
+impl From<GrantEventV1> for GrantEventV2 {
+    fn from(event: GrantEventV1) -> Self {
+        Self {
+            owner: event.owner,
+            grantee: event.grantee,
+            data_id: event.data_id,
+            scope: event.scope,
+            locked_until: event.locked_until,
+            purpose: None,
+            access: None,
+        }
+    }
+}
+
+// Version 3 of `grant_inserted` added `consent_hash`.
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantEventV3 {
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub scope: GrantScope,
+    pub locked_until: EpochHeight,
+    pub purpose: Option<String>,
+    pub access: Option<AccessScope>,
+    pub consent_hash: Option<ConsentHash>,
+}
+
+impl From<GrantEventV2> for GrantEventV3 {
+    fn from(event: GrantEventV2) -> Self {
+        Self {
+            owner: event.owner,
+            grantee: event.grantee,
+            data_id: event.data_id,
+            scope: event.scope,
+            locked_until: event.locked_until,
+            purpose: event.purpose,
+            access: event.access,
+            consent_hash: None,
+        }
+    }
+}
+
+// Version 4 of `grant_inserted` added `valid_from`.
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantEventV4 {
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub scope: GrantScope,
+    pub locked_until: EpochHeight,
+    pub purpose: Option<String>,
+    pub access: Option<AccessScope>,
+    pub consent_hash: Option<ConsentHash>,
+    pub valid_from: Timestamp,
+}
+
+impl From<GrantEventV3> for GrantEventV4 {
+    fn from(event: GrantEventV3) -> Self {
+        Self {
+            owner: event.owner,
+            grantee: event.grantee,
+            data_id: event.data_id,
+            scope: event.scope,
+            locked_until: event.locked_until,
+            purpose: event.purpose,
+            access: event.access,
+            consent_hash: event.consent_hash,
+            valid_from: 0,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GrantInserted {
+    V0(GrantEventV0),
+    V1(GrantEventV1),
+    V2(GrantEventV2),
+    V3(GrantEventV3),
+    V4(GrantEventV4),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GrantDeleted {
+    V0(GrantEventV0),
+    V1(GrantEventV1),
+}
+
+impl GrantInserted {
+    // The event's data as of the latest version.
+    pub fn into_latest(self) -> GrantEventV4 {
+        match self {
+            Self::V0(event) => {
+                GrantEventV3::from(GrantEventV2::from(GrantEventV1::from(event))).into()
+            }
+            Self::V1(event) => GrantEventV3::from(GrantEventV2::from(event)).into(),
+            Self::V2(event) => GrantEventV3::from(event).into(),
+            Self::V3(event) => event.into(),
+            Self::V4(event) => event,
+        }
+    }
+}
+
+impl GrantDeleted {
+    // The event's data as of the latest version.
+    pub fn into_latest(self) -> GrantEventV1 {
+        match self {
+            Self::V0(event) => event.into(),
+            Self::V1(event) => event,
+        }
+    }
+}
+
+// Same data as `grant_deleted`, but it was the grantee giving the grant up.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GrantRenounced {
+    V0(GrantEventV1),
+}
+
+impl GrantRenounced {
+    // The event's data as of the latest version.
+    pub fn into_latest(self) -> GrantEventV1 {
+        match self {
+            Self::V0(event) => event,
+        }
+    }
+}
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantLockExtendedV0 {
+    pub grant_id: String,
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub scope: GrantScope,
+    pub previous_locked_until: EpochHeight,
+    pub locked_until: EpochHeight,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GrantLockExtended {
+    V0(GrantLockExtendedV0),
+}
+
+impl GrantLockExtended {
+    // The event's data as of the latest version.
+    pub fn into_latest(self) -> GrantLockExtendedV0 {
+        match self {
+            Self::V0(event) => event,
+        }
+    }
+}
+
+// The key itself isn't in the event: the grantee fetches it with `get_grant_key`.
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantKeySetV0 {
+    pub grant_id: String,
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GrantKeySet {
+    V0(GrantKeySetV0),
+}
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EncryptionKeyPublishedV0 {
+    pub grantee: PublicKey,
+    pub encryption_key: EncryptionKey,
+    pub rotation: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EncryptionKeyPublished {
+    V0(EncryptionKeyPublishedV0),
+}
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GranteeProfileRegisteredV0 {
+    pub grantee: PublicKey,
+    pub name: String,
+    pub url: String,
+    pub logo_hash: Option<ContentHash>,
+    pub privacy_policy_hash: Option<ContentHash>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GranteeProfileRegistered {
+    V0(GranteeProfileRegisteredV0),
+}
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GranteeVerifiedV0 {
+    pub grantee: PublicKey,
+    pub verified: bool,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GranteeVerified {
+    V0(GranteeVerifiedV0),
+}
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GranteeListUpdatedV0 {
+    pub owner: PublicKey,
+    pub list: GranteeList,
+    pub grantee: PublicKey,
+    pub listed: bool,
+    pub revision: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GranteeListUpdated {
+    V0(GranteeListUpdatedV0),
+}
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AllowListEnabledV0 {
+    pub owner: PublicKey,
+    pub enabled: bool,
+    pub revision: u32,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AllowListEnabled {
+    V0(AllowListEnabledV0),
+}
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockPoliciesSetV0 {
+    pub grantee: PublicKey,
+    pub policies: Vec<LockPolicy>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum LockPoliciesSet {
+    V0(LockPoliciesSetV0),
+}
+
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccessRequestedV0 {
+    pub id: u64,
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    pub purpose: String,
+    pub desired_lock: Option<EpochHeight>,
+    pub expires_at: Timestamp,
+}
+
+// For both approvals and rejections. An approval also comes with its own `grant_inserted`.
+#[derive(Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccessRequestResolvedV0 {
+    pub id: u64,
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AccessRequested {
+    V0(AccessRequestedV0),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AccessRequestApproved {
+    V0(AccessRequestResolvedV0),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum AccessRequestRejected {
+    V0(AccessRequestResolvedV0),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FractalRegistryEvent {
+    GrantInserted(GrantInserted),
+    GrantDeleted(GrantDeleted),
+    GrantRenounced(GrantRenounced),
+    GrantLockExtended(GrantLockExtended),
+    GrantKeySet(GrantKeySet),
+    EncryptionKeyPublished(EncryptionKeyPublished),
+    GranteeProfileRegistered(GranteeProfileRegistered),
+    GranteeVerified(GranteeVerified),
+    GranteeListUpdated(GranteeListUpdated),
+    AllowListEnabled(AllowListEnabled),
+    LockPoliciesSet(LockPoliciesSet),
+    AccessRequested(AccessRequested),
+    AccessRequestApproved(AccessRequestApproved),
+    AccessRequestRejected(AccessRequestRejected),
+}
+
+#[derive(Debug)]
+pub enum EventDecodeError {
+    Json(serde_json::Error),
+    UnknownEvent { event: String, version: String },
+}
+
+impl fmt::Display for EventDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "Invalid {} event JSON: {}", EVENT_STANDARD, error),
+            Self::UnknownEvent { event, version } => write!(
+                f,
+                "Unknown {} event {:?} version {:?}",
+                EVENT_STANDARD, event, version
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EventDecodeError {}
+
+impl From<serde_json::Error> for EventDecodeError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventEnvelope {
+    standard: String,
+    version: String,
+    event: String,
+    data: Value,
+}
+
+// Returns `Ok(None)` for logs that aren't `FractalRegistry` events at all.
+pub fn decode_event(log: &str) -> Result<Option<FractalRegistryEvent>, EventDecodeError> {
+    let Some((EVENT_JSON_PREFIX, json_str)) = log.split_once(EVENT_JSON_SEPARATOR) else {
+        return Ok(None);
+    };
+
+    let envelope: EventEnvelope = serde_json::from_str(json_str)?;
+    if envelope.standard != EVENT_STANDARD {
+        return Ok(None);
+    }
+
+    let data = envelope.data;
+
+    let event = match (envelope.event.as_str(), envelope.version.as_str()) {
+        ("grant_inserted", "0") => {
+            FractalRegistryEvent::GrantInserted(GrantInserted::V0(serde_json::from_value(data)?))
+        }
+        ("grant_inserted", "1") => {
+            FractalRegistryEvent::GrantInserted(GrantInserted::V1(serde_json::from_value(data)?))
+        }
+        ("grant_inserted", "2") => {
+            FractalRegistryEvent::GrantInserted(GrantInserted::V2(serde_json::from_value(data)?))
+        }
+        ("grant_inserted", "3") => {
+            FractalRegistryEvent::GrantInserted(GrantInserted::V3(serde_json::from_value(data)?))
+        }
+        ("grant_inserted", "4") => {
+            FractalRegistryEvent::GrantInserted(GrantInserted::V4(serde_json::from_value(data)?))
+        }
+        ("grant_deleted", "0") => {
+            FractalRegistryEvent::GrantDeleted(GrantDeleted::V0(serde_json::from_value(data)?))
+        }
+        ("grant_deleted", "1") => {
+            FractalRegistryEvent::GrantDeleted(GrantDeleted::V1(serde_json::from_value(data)?))
+        }
+        ("grant_renounced", "0") => {
+            FractalRegistryEvent::GrantRenounced(GrantRenounced::V0(serde_json::from_value(data)?))
+        }
+        ("grant_key_set", "0") => {
+            FractalRegistryEvent::GrantKeySet(GrantKeySet::V0(serde_json::from_value(data)?))
+        }
+        ("grant_lock_extended", "0") => FractalRegistryEvent::GrantLockExtended(
+            GrantLockExtended::V0(serde_json::from_value(data)?),
+        ),
+        ("encryption_key_published", "0") => FractalRegistryEvent::EncryptionKeyPublished(
+            EncryptionKeyPublished::V0(serde_json::from_value(data)?),
+        ),
+        ("grantee_profile_registered", "0") => FractalRegistryEvent::GranteeProfileRegistered(
+            GranteeProfileRegistered::V0(serde_json::from_value(data)?),
+        ),
+        ("grantee_verified", "0") => FractalRegistryEvent::GranteeVerified(GranteeVerified::V0(
+            serde_json::from_value(data)?,
+        )),
+        ("grantee_list_updated", "0") => FractalRegistryEvent::GranteeListUpdated(
+            GranteeListUpdated::V0(serde_json::from_value(data)?),
+        ),
+        ("allow_list_enabled", "0") => FractalRegistryEvent::AllowListEnabled(
+            AllowListEnabled::V0(serde_json::from_value(data)?),
+        ),
+        ("lock_policies_set", "0") => FractalRegistryEvent::LockPoliciesSet(LockPoliciesSet::V0(
+            serde_json::from_value(data)?,
+        )),
+        ("access_requested", "0") => FractalRegistryEvent::AccessRequested(AccessRequested::V0(
+            serde_json::from_value(data)?,
+        )),
+        ("access_request_approved", "0") => FractalRegistryEvent::AccessRequestApproved(
+            AccessRequestApproved::V0(serde_json::from_value(data)?),
+        ),
+        ("access_request_rejected", "0") => FractalRegistryEvent::AccessRequestRejected(
+            AccessRequestRejected::V0(serde_json::from_value(data)?),
+        ),
+        (event, version) => {
+            return Err(EventDecodeError::UnknownEvent {
+                event: event.into(),
+                version: version.into(),
+            })
+        }
+    };
+
+    Ok(Some(event))
+}
+
+pub fn decode_events<'a>(
+    logs: impl IntoIterator<Item = &'a str>,
+) -> Result<Vec<FractalRegistryEvent>, EventDecodeError> {
+    logs.into_iter()
+        .filter_map(|log| decode_event(log).transpose())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_grant_inserted_v0() {
+        let log = r#"EVENT_JSON:{"standard":"FractalRegistry","version":"0","event":"grant_inserted","data":{"owner":"ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ","grantee":"ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi","data_id":"some data","locked_until":1337}}"#;
+
+        assert_eq!(
+            decode_event(log).unwrap(),
+            Some(FractalRegistryEvent::GrantInserted(GrantInserted::V0(
+                GrantEventV0 {
+                    owner: "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+                        .parse()
+                        .unwrap(),
+                    grantee: "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+                        .parse()
+                        .unwrap(),
+                    data_id: "some data".into(),
+                    locked_until: 1337,
+                }
+            )))
+        );
+    }
+
+    #[test]
+    fn decode_grant_deleted_v1() {
+        let log = r#"EVENT_JSON:{"standard":"FractalRegistry","version":"1","event":"grant_deleted","data":{"owner":"ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ","grantee":"ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi","data_id":"creds/","scope":"prefix","locked_until":0}}"#;
+
+        let Some(FractalRegistryEvent::GrantDeleted(event)) = decode_event(log).unwrap() else {
+            panic!("expected a grant_deleted event");
+        };
+        let event = event.into_latest();
+
+        assert_eq!(event.data_id, "creds/");
+        assert_eq!(event.scope, GrantScope::Prefix);
+    }
+
+    #[test]
+    fn decode_grant_inserted_v2() {
+        let log = r#"EVENT_JSON:{"standard":"FractalRegistry","version":"2","event":"grant_inserted","data":{"owner":"ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ","grantee":"ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi","data_id":"A1","scope":"exact","locked_until":0,"purpose":"kyc","access":{"mode":"read_only","fields":["name"]}}}"#;
+
+        let Some(FractalRegistryEvent::GrantInserted(event)) = decode_event(log).unwrap() else {
+            panic!("expected a grant_inserted event");
+        };
+        let event = event.into_latest();
+
+        assert_eq!(event.purpose.as_deref(), Some("kyc"));
+        assert_eq!(
+            event.access,
+            Some(AccessScope {
+                mode: crate::AccessMode::ReadOnly,
+                fields: vec!["name".into()],
+            })
+        );
+        assert_eq!(event.valid_from, 0);
+    }
+
+    #[test]
+    fn skip_unrelated_logs() {
+        assert_eq!(decode_event("just a log line").unwrap(), None);
+        assert_eq!(
+            decode_event(
+                r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[]}"#
+            )
+            .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn reject_unknown_versions() {
+        assert!(matches!(
+            decode_event(
+                r#"EVENT_JSON:{"standard":"FractalRegistry","version":"99","event":"grant_inserted","data":{}}"#
+            ),
+            Err(EventDecodeError::UnknownEvent { .. })
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+// `nonce`, `signature`, `consent_hash`, `encryption_key` and `content_hash` as they travel in JSON arguments: a base64 or
+// base58 string, or the array of numbers older clients send. We always serialize to base64.
+
+use std::convert::TryInto;
+use std::fmt;
+use std::marker::PhantomData;
+
+use near_sdk::base64::engine::general_purpose::STANDARD as BASE64;
+use near_sdk::base64::Engine;
+use near_sdk::borsh::{BorshDeserialize, BorshSerialize};
+use near_sdk::bs58;
+use near_sdk::serde::de::{self, SeqAccess, Visitor};
+use near_sdk::serde::{Deserialize, Deserializer, Serialize, Serializer};
+use near_sdk::NearSchema;
+
+#[derive(BorshDeserialize, BorshSerialize, NearSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(transparent)]
+pub struct Nonce(#[schemars(with = "String")] pub [u8; 32]);
+
+#[derive(BorshDeserialize, BorshSerialize, NearSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(transparent)]
+pub struct Ed25519Signature(#[schemars(with = "String")] pub [u8; 64]);
+
+// SHA-256 of the consent text an owner was shown when granting.
+#[derive(BorshDeserialize, BorshSerialize, NearSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(transparent)]
+pub struct ConsentHash(#[schemars(with = "String")] pub [u8; 32]);
+
+// An X25519 public key grantees get data encrypted to. Unlike their ed25519 key, it never signs anything.
+#[derive(BorshDeserialize, BorshSerialize, NearSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(transparent)]
+pub struct EncryptionKey(#[schemars(with = "String")] pub [u8; 32]);
+
+// SHA-256 of some file kept off-chain, like a grantee's logo, so whoever fetches it can tell it's the one meant.
+#[derive(BorshDeserialize, BorshSerialize, NearSchema, Clone, Copy, PartialEq, Eq, Debug)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(transparent)]
+pub struct ContentHash(#[schemars(with = "String")] pub [u8; 32]);
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FixedBytesError {
+    Encoding,
+    Length { expected: usize, actual: usize },
+}
+
+impl fmt::Display for FixedBytesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encoding => write!(f, "expected a base64 or base58 string"),
+            Self::Length { expected, actual } => {
+                write!(f, "expected {} bytes, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FixedBytesError {}
+
+// Padded base64 for a given length never decodes as base58 to that same length (and the other way around), since
+// base58 has no `=` and the string lengths differ. So trying both is unambiguous.
+pub fn decode_fixed_bytes<const N: usize>(encoded: &str) -> Result<[u8; N], FixedBytesError> {
+    let candidates = [
+        BASE64.decode(encoded).ok(),
+        bs58::decode(encoded).into_vec().ok(),
+    ];
+
+    if let Some(bytes) = candidates.iter().flatten().find(|bytes| bytes.len() == N) {
+        return Ok(bytes.as_slice().try_into().unwrap());
+    }
+
+    match candidates.iter().flatten().next() {
+        Some(bytes) => Err(FixedBytesError::Length {
+            expected: N,
+            actual: bytes.len(),
+        }),
+        None => Err(FixedBytesError::Encoding),
+    }
+}
+
+struct FixedBytesVisitor<const N: usize> {
+    name: &'static str,
+    marker: PhantomData<[u8; N]>,
+}
+
+impl<'de, const N: usize> Visitor<'de> for FixedBytesVisitor<N> {
+    type Value = [u8; N];
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} as {} bytes in base64, base58 or an array of numbers",
+            self.name, N
+        )
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        decode_fixed_bytes(value).map_err(|e| E::custom(format!("invalid {}: {}", self.name, e)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(N);
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+
+        let actual = bytes.len();
+        bytes.try_into().map_err(|_| {
+            de::Error::custom(format!(
+                "invalid {}: {}",
+                self.name,
+                FixedBytesError::Length {
+                    expected: N,
+                    actual
+                }
+            ))
+        })
+    }
+}
+
+macro_rules! fixed_bytes_serde {
+    ( $type:ident, $len:literal, $name:literal ) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&BASE64.encode(self.0))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer
+                    .deserialize_any(FixedBytesVisitor::<$len> {
+                        name: $name,
+                        marker: PhantomData,
+                    })
+                    .map(Self)
+            }
+        }
+
+        impl From<[u8; $len]> for $type {
+            fn from(bytes: [u8; $len]) -> Self {
+                Self(bytes)
+            }
+        }
+    };
+}
+
+fixed_bytes_serde!(Nonce, 32, "nonce");
+fixed_bytes_serde!(Ed25519Signature, 64, "signature");
+fixed_bytes_serde!(ConsentHash, 32, "consent_hash");
+fixed_bytes_serde!(EncryptionKey, 32, "encryption_key");
+fixed_bytes_serde!(ContentHash, 32, "content_hash");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::serde_json::{self, json};
+
+    #[test]
+    fn deserialize_all_encodings() {
+        let bytes: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let expected = Nonce(bytes);
+
+        for encoded in [
+            json!(bytes),
+            json!(BASE64.encode(bytes)),
+            json!(bs58::encode(bytes).into_string()),
+        ] {
+            assert_eq!(serde_json::from_value::<Nonce>(encoded).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn serialize_to_base64() {
+        let signature = Ed25519Signature([7; 64]);
+        let encoded = serde_json::to_value(signature).unwrap();
+
+        assert_eq!(encoded, json!(BASE64.encode([7; 64])));
+        assert_eq!(
+            serde_json::from_value::<Ed25519Signature>(encoded).unwrap(),
+            signature
+        );
+    }
+
+    #[test]
+    fn reject_wrong_lengths_and_encodings() {
+        let error = |value| {
+            serde_json::from_value::<Ed25519Signature>(value)
+                .unwrap_err()
+                .to_string()
+        };
+
+        assert_eq!(
+            error(json!(vec![0; 63])),
+            "invalid signature: expected 64 bytes, got 63"
+        );
+        assert_eq!(
+            error(json!(BASE64.encode([0; 65]))),
+            "invalid signature: expected 64 bytes, got 65"
+        );
+        assert_eq!(
+            error(json!("not base-anything!")),
+            "invalid signature: expected a base64 or base58 string"
+        );
+    }
+}
@@ -0,0 +1,898 @@
+// Everything here has to build both for the contract (wasm) and for host-side tools, so no `near_sdk::env`.
+
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+pub use near_sdk::json_types::Base64VecU8;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::NearSchema;
+pub use near_sdk::{Duration, EpochHeight, PublicKey, Timestamp};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+
+mod errors;
+pub mod events;
+mod fixed_bytes;
+pub mod merkle;
+
+pub use errors::RegistryError;
+pub use fixed_bytes::{
+    decode_fixed_bytes, ConsentHash, ContentHash, Ed25519Signature, EncryptionKey, FixedBytesError,
+    Nonce,
+};
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Grant {
+    pub owner: PublicKey,
+    pub grantee: PublicKey,
+    pub data_id: String,
+    #[serde(default)]
+    pub scope: GrantScope,
+    pub locked_until: EpochHeight,
+    // Why the grantee may use the data, and how. Neither is part of the grant id.
+    #[serde(default)]
+    pub purpose: Option<String>,
+    #[serde(default)]
+    pub access: Option<AccessScope>,
+    // Ties the grant to the exact terms the owner agreed to, without putting them on chain.
+    #[serde(default)]
+    pub consent_hash: Option<ConsentHash>,
+    // When the grantee's access starts, with 0 meaning right away. Not part of the grant id either.
+    #[serde(default)]
+    pub valid_from: Timestamp,
+}
+
+impl Grant {
+    pub fn covers(&self, data_id: &str) -> bool {
+        match self.scope {
+            GrantScope::Exact => self.data_id == data_id,
+            GrantScope::Prefix => data_id.starts_with(&self.data_id),
+        }
+    }
+
+    pub fn is_active(&self, now: Timestamp) -> bool {
+        self.valid_from <= now
+    }
+
+    // Grants without an `access` scope predate it, and don't restrict anything.
+    pub fn allows(&self, mode: AccessMode) -> bool {
+        self.access.as_ref().map_or(true, |access| {
+            access.mode == AccessMode::ReadWrite || access.mode == mode
+        })
+    }
+}
+
+// Whether a grant's `data_id` is the one data id it covers, or a prefix (like `creds/`) covering every data id
+// that starts with it.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum GrantScope {
+    #[default]
+    Exact,
+    Prefix,
+}
+
+// What a grant lets the grantee do with the data. An empty `fields` means every field.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccessScope {
+    pub mode: AccessMode,
+    #[serde(default)]
+    pub fields: Vec<String>,
+}
+
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum AccessMode {
+    ReadOnly,
+    ReadWrite,
+}
+
+impl AccessMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReadOnly => "read_only",
+            Self::ReadWrite => "read_write",
+        }
+    }
+}
+
+// An owner's say over which grantees may get their grants: never the ones they deny and, once they allow any, only
+// those.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum GranteeList {
+    Allow,
+    Deny,
+}
+
+impl GranteeList {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+pub const MAX_GRANTEE_LIST_LENGTH: usize = 100;
+
+// Why a grant in the contract's history is gone.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum DeletionReason {
+    Deleted,
+    RevokedAll,
+    Renounced,
+}
+
+// What happened to a grant in the contract's change log.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde", rename_all = "snake_case")]
+pub enum ChangeKind {
+    Inserted,
+    Deleted,
+    // `grant` is as it is after the extension.
+    LockExtended,
+}
+
+// A grantee's minimum timelock for grants of data under `data_id_prefix` (an empty one covers everything), like
+// the years a regulated grantee has to retain KYC data.
+#[derive(
+    BorshDeserialize,
+    BorshSerialize,
+    Serialize,
+    Deserialize,
+    NearSchema,
+    Clone,
+    PartialEq,
+    Eq,
+    Debug,
+)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct LockPolicy {
+    pub data_id_prefix: String,
+    pub min_lock_duration: Duration,
+}
+
+impl LockPolicy {
+    // Whether a grant of `data_id` (or of everything under it, for prefix grants) covers any data this policy is
+    // about.
+    pub fn applies_to(&self, data_id: &str, scope: GrantScope) -> bool {
+        data_id.starts_with(&self.data_id_prefix)
+            || (scope == GrantScope::Prefix && self.data_id_prefix.starts_with(data_id))
+    }
+}
+
+pub const MAX_LOCK_POLICIES: usize = 32;
+
+pub const MAX_PURPOSE_LENGTH: usize = 64;
+pub const MAX_ACCESS_FIELDS: usize = 32;
+pub const MAX_ACCESS_FIELD_LENGTH: usize = 64;
+
+// Both end up on their own lines of signed messages, so they can't smuggle in lines (or, for fields, list items)
+// of their own.
+pub fn validate_purpose(purpose: &str) -> Result<(), RegistryError> {
+    if purpose.is_empty()
+        || purpose.len() > MAX_PURPOSE_LENGTH
+        || purpose.chars().any(char::is_control)
+    {
+        return Err(RegistryError::InvalidPurpose);
+    }
+
+    Ok(())
+}
+
+impl AccessScope {
+    pub fn validate(&self) -> Result<(), RegistryError> {
+        let valid_field = |field: &String| {
+            !field.is_empty()
+                && field.len() <= MAX_ACCESS_FIELD_LENGTH
+                && !field.chars().any(|c| c.is_control() || c == ',')
+        };
+
+        if self.fields.len() > MAX_ACCESS_FIELDS || !self.fields.iter().all(valid_field) {
+            return Err(RegistryError::InvalidAccessScope);
+        }
+
+        Ok(())
+    }
+}
+
+pub const MAX_PROFILE_NAME_LENGTH: usize = 64;
+pub const MAX_PROFILE_URL_LENGTH: usize = 256;
+
+// Wallets show these next to the grantee's key, so no control characters to mess with the display, and only https
+// links.
+pub fn validate_grantee_profile(name: &str, url: &str) -> Result<(), RegistryError> {
+    if name.is_empty()
+        || name.len() > MAX_PROFILE_NAME_LENGTH
+        || name.chars().any(char::is_control)
+        || !url.starts_with("https://")
+        || url.len() > MAX_PROFILE_URL_LENGTH
+        || url.chars().any(|c| c.is_control() || c.is_whitespace())
+    {
+        return Err(RegistryError::InvalidGranteeProfile);
+    }
+
+    Ok(())
+}
+
+pub fn consent_hash(text: &str) -> ConsentHash {
+    ConsentHash(Sha256::digest(text.as_bytes()).into())
+}
+
+pub fn derive_grant_id(grant: &Grant) -> String {
+    // No public key string starts with `prefix:`, so prefix grants can't collide with exact ones. The lock isn't part
+    // of it, so extending one keeps the grant's id.
+    let scope_tag = match grant.scope {
+        GrantScope::Exact => "",
+        GrantScope::Prefix => "prefix:",
+    };
+
+    let id = format!(
+        "{}{}{}{}",
+        scope_tag,
+        Into::<String>::into(&grant.owner),
+        Into::<String>::into(&grant.grantee),
+        grant.data_id,
+    );
+
+    hex::encode(Keccak256::digest(id.as_bytes()))
+}
+
+pub const GRANT_MESSAGE_RECIPIENT: &str = "idos.network";
+
+#[allow(clippy::too_many_arguments)]
+pub fn insert_grant_message(
+    owner: &PublicKey,
+    grantee: &PublicKey,
+    data_id: &str,
+    locked_until: Option<EpochHeight>,
+    purpose: Option<&str>,
+    access: Option<&AccessScope>,
+    consent_hash: Option<&ConsentHash>,
+    valid_from: Option<Timestamp>,
+) -> String {
+    grant_operation_message(
+        "insertGrant",
+        owner,
+        grantee,
+        data_id,
+        GrantScope::Exact,
+        locked_until,
+    ) + &grant_terms_lines(purpose, access, consent_hash, valid_from)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn insert_prefix_grant_message(
+    owner: &PublicKey,
+    grantee: &PublicKey,
+    data_id_prefix: &str,
+    locked_until: Option<EpochHeight>,
+    purpose: Option<&str>,
+    access: Option<&AccessScope>,
+    consent_hash: Option<&ConsentHash>,
+    valid_from: Option<Timestamp>,
+) -> String {
+    grant_operation_message(
+        "insertGrant",
+        owner,
+        grantee,
+        data_id_prefix,
+        GrantScope::Prefix,
+        locked_until,
+    ) + &grant_terms_lines(purpose, access, consent_hash, valid_from)
+}
+
+pub fn delete_grant_message(
+    owner: &PublicKey,
+    grantee: &PublicKey,
+    data_id: &str,
+    locked_until: Option<EpochHeight>,
+) -> String {
+    grant_operation_message(
+        "deleteGrant",
+        owner,
+        grantee,
+        data_id,
+        GrantScope::Exact,
+        locked_until,
+    )
+}
+
+pub fn delete_prefix_grant_message(
+    owner: &PublicKey,
+    grantee: &PublicKey,
+    data_id_prefix: &str,
+    locked_until: Option<EpochHeight>,
+) -> String {
+    grant_operation_message(
+        "deleteGrant",
+        owner,
+        grantee,
+        data_id_prefix,
+        GrantScope::Prefix,
+        locked_until,
+    )
+}
+
+// Signed by the grantee, not the owner. `revision` counts the grantee's renounces of the owner's grants for
+// `data_id` (starting at 1), so a renounce signed for a grant can't be replayed on one given again under its old id.
+pub fn renounce_grant_message(
+    owner: &PublicKey,
+    grantee: &PublicKey,
+    data_id: &str,
+    revision: u32,
+) -> String {
+    format!(
+        "operation: renounceGrant\n\
+        owner: {}\n\
+        grantee: {}\n\
+        dataId: {}\n\
+        revision: {}",
+        Into::<String>::into(owner),
+        Into::<String>::into(grantee),
+        data_id,
+        revision,
+    )
+}
+
+// The grant's data content key, encrypted for the grantee. It's opaque to the contract, so the cap is only there to
+// bound storage.
+pub const MAX_GRANT_KEY_LENGTH: usize = 1024;
+
+// Blobs don't belong in a message anyone reads, so it's signed by hash. `revision` counts the grant's key updates
+// (starting at 1), so an old signature can't roll a rotated key back.
+pub fn set_grant_key_message(
+    owner: &PublicKey,
+    grant_id: &str,
+    encrypted_key: &[u8],
+    revision: u32,
+) -> String {
+    format!(
+        "operation: setGrantKey\n\
+        owner: {}\n\
+        grantId: {}\n\
+        encryptedKeyHash: {}\n\
+        revision: {}",
+        Into::<String>::into(owner),
+        grant_id,
+        hex::encode(Sha256::digest(encrypted_key)),
+        revision,
+    )
+}
+
+// Signed by the grantee. `rotation` counts the grantee's published keys (starting at 1), so an old signature can't
+// be replayed to bring back a key the grantee moved away from.
+pub fn publish_encryption_key_message(
+    grantee: &PublicKey,
+    encryption_key: &EncryptionKey,
+    rotation: u32,
+) -> String {
+    format!(
+        "operation: publishEncryptionKey\n\
+        grantee: {}\n\
+        encryptionKey: {}\n\
+        rotation: {}",
+        Into::<String>::into(grantee),
+        hex::encode(encryption_key.0),
+        rotation,
+    )
+}
+
+// `revision` counts the owner's list updates (starting at 1), so a relayer can't replay an old one, like taking a
+// grantee back off the deny list after the owner put them on it again.
+pub fn update_grantee_list_message(
+    owner: &PublicKey,
+    list: GranteeList,
+    grantee: &PublicKey,
+    listed: bool,
+    revision: u32,
+) -> String {
+    format!(
+        "operation: updateGranteeList\n\
+        owner: {}\n\
+        list: {}\n\
+        grantee: {}\n\
+        listed: {}\n\
+        revision: {}",
+        Into::<String>::into(owner),
+        list.as_str(),
+        Into::<String>::into(grantee),
+        listed,
+        revision,
+    )
+}
+
+// Shares `revision` with `update_grantee_list_message`: both count the owner's list updates.
+pub fn set_allow_list_enabled_message(owner: &PublicKey, enabled: bool, revision: u32) -> String {
+    format!(
+        "operation: setAllowListEnabled\n\
+        owner: {}\n\
+        enabled: {}\n\
+        revision: {}",
+        Into::<String>::into(owner),
+        enabled,
+        revision,
+    )
+}
+
+// `revision` counts the grant's lock extensions (starting at 1). A grant given again gets its old id back, so without
+// it an extension signed for the old grant could be replayed on the new one.
+pub fn extend_lock_message(
+    owner: &PublicKey,
+    grant_id: &str,
+    new_locked_until: EpochHeight,
+    revision: u32,
+) -> String {
+    format!(
+        "operation: extendLock\n\
+        owner: {}\n\
+        grantId: {}\n\
+        lockedUntil: {}\n\
+        revision: {}",
+        Into::<String>::into(owner),
+        grant_id,
+        new_locked_until,
+        revision,
+    )
+}
+
+// Signing this lets anyone revoke every (non-timelocked) grant of `owner` for `grantee`, or for all grantees when
+// there's none, across as many calls as it takes. Only once, though: `revision` gets used up when that's done.
+pub fn revoke_all_message(owner: &PublicKey, grantee: Option<&PublicKey>, revision: u32) -> String {
+    format!(
+        "operation: revokeAll\n\
+        owner: {}\n\
+        grantee: {}\n\
+        revision: {}",
+        Into::<String>::into(owner),
+        grantee.map_or_else(|| "*".into(), Into::<String>::into),
+        revision,
+    )
+}
+
+fn grant_operation_message(
+    operation: &str,
+    owner: &PublicKey,
+    grantee: &PublicKey,
+    data_id: &str,
+    scope: GrantScope,
+    locked_until: Option<EpochHeight>,
+) -> String {
+    // The signer has to see what they're granting, so a prefix is spelled out as such.
+    let data_id_label = match scope {
+        GrantScope::Exact => "dataId",
+        GrantScope::Prefix => "dataIdPrefix",
+    };
+
+    format!(
+        "operation: {}\n\
+        owner: {}\n\
+        grantee: {}\n\
+        {}: {}\n\
+        lockedUntil: {}",
+        operation,
+        Into::<String>::into(owner),
+        Into::<String>::into(grantee),
+        data_id_label,
+        data_id,
+        locked_until.unwrap_or(0)
+    )
+}
+
+// Only there when given, so messages for grants without them read as they always did.
+fn grant_terms_lines(
+    purpose: Option<&str>,
+    access: Option<&AccessScope>,
+    consent_hash: Option<&ConsentHash>,
+    valid_from: Option<Timestamp>,
+) -> String {
+    let mut lines = String::new();
+
+    if let Some(purpose) = purpose {
+        lines += &format!("\npurpose: {}", purpose);
+    }
+
+    if let Some(access) = access {
+        lines += &format!("\naccess: {}", access.mode.as_str());
+
+        if !access.fields.is_empty() {
+            lines += &format!("\nfields: {}", access.fields.join(","));
+        }
+    }
+
+    // In hex, to compare against what `sha256sum` says about the terms.
+    if let Some(consent_hash) = consent_hash {
+        lines += &format!("\nconsentHash: {}", hex::encode(consent_hash.0));
+    }
+
+    // Left out when it's 0, so messages for grants active right away read as they always did.
+    if let Some(valid_from) = valid_from.filter(|valid_from| *valid_from > 0) {
+        lines += &format!("\nvalidFrom: {}", valid_from);
+    }
+
+    lines
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[borsh(crate = "near_sdk::borsh")]
+#[serde(crate = "near_sdk::serde")]
+pub struct Nep413Payload {
+    pub message: String,
+    pub nonce: [u8; 32],
+    pub recipient: String,
+    #[serde(rename = "callbackUrl")]
+    pub callback_url: Option<String>,
+}
+
+pub const NEP413_TAG: u32 = 2147484061; // 2**31 + 413
+
+pub fn nep413_hashed_payload(payload: &Nep413Payload) -> [u8; 32] {
+    let mut writer = vec![];
+
+    borsh::to_writer(&mut writer, &NEP413_TAG).expect("Can't borsh encode NEP413_TAG");
+    borsh::to_writer(&mut writer, payload).expect("Can't borsh encode payload");
+
+    Sha256::digest(&writer).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn owner() -> PublicKey {
+        "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+            .parse()
+            .unwrap()
+    }
+
+    fn grantee() -> PublicKey {
+        "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn derive_grant_id_example() {
+        // Same expectation as the contract's, which used to hash through `env::keccak256`.
+
+        let grant = Grant {
+            owner: owner(),
+            grantee: grantee(),
+            data_id: "some data".into(),
+            scope: GrantScope::Exact,
+            locked_until: 1337,
+            purpose: None,
+            access: None,
+            consent_hash: None,
+            valid_from: 0,
+        };
+
+        assert_eq!(
+            "34c755f71143a5dd4a9c3c0425f3d573c86579b586e72dd8ef04e1b41606c657",
+            derive_grant_id(&grant)
+        );
+        assert_ne!(
+            derive_grant_id(&grant),
+            derive_grant_id(&Grant {
+                scope: GrantScope::Prefix,
+                ..grant.clone()
+            })
+        );
+        assert_eq!(
+            derive_grant_id(&grant),
+            derive_grant_id(&Grant {
+                purpose: Some("kyc".into()),
+                locked_until: 1338,
+                valid_from: 1_000,
+                ..grant
+            })
+        );
+    }
+
+    #[test]
+    fn prefix_grants_cover_data_ids_under_them() {
+        let grant = Grant {
+            owner: owner(),
+            grantee: grantee(),
+            data_id: "creds/".into(),
+            scope: GrantScope::Prefix,
+            locked_until: 0,
+            purpose: None,
+            access: None,
+            consent_hash: None,
+            valid_from: 0,
+        };
+
+        assert!(grant.covers("creds/"));
+        assert!(grant.covers("creds/passport"));
+        assert!(!grant.covers("creds"));
+        assert!(!grant.covers("other/creds/passport"));
+        assert!(!Grant {
+            scope: GrantScope::Exact,
+            ..grant.clone()
+        }
+        .covers("creds/passport"));
+    }
+
+    #[test]
+    fn insert_grant_message_example() {
+        assert_eq!(
+            insert_grant_message(
+                &owner(),
+                &grantee(),
+                "some data",
+                None,
+                None,
+                None,
+                None,
+                None
+            ),
+            "operation: insertGrant\n\
+            owner: ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ\n\
+            grantee: ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi\n\
+            dataId: some data\n\
+            lockedUntil: 0"
+        );
+        assert_eq!(
+            insert_prefix_grant_message(
+                &owner(),
+                &grantee(),
+                "creds/",
+                Some(1337),
+                None,
+                None,
+                None,
+                Some(0)
+            ),
+            "operation: insertGrant\n\
+            owner: ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ\n\
+            grantee: ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi\n\
+            dataIdPrefix: creds/\n\
+            lockedUntil: 1337"
+        );
+        assert!(insert_grant_message(
+            &owner(),
+            &grantee(),
+            "some data",
+            None,
+            Some("kyc"),
+            Some(&AccessScope {
+                mode: AccessMode::ReadOnly,
+                fields: vec!["name".into(), "dob".into()],
+            }),
+            Some(&consent_hash("I agree")),
+            Some(1_800_000_000_000_000_000)
+        )
+        .ends_with(
+            "lockedUntil: 0\n\
+            purpose: kyc\n\
+            access: read_only\n\
+            fields: name,dob\n\
+            consentHash: 99955b75e054416577b88fcec811270c93bff6f1bc421f01e3da7c1b18ec61e3\n\
+            validFrom: 1800000000000000000"
+        ));
+    }
+
+    #[test]
+    fn purposes_and_access_fields_stay_on_one_line() {
+        assert_eq!(validate_purpose("kyc"), Ok(()));
+        assert_eq!(
+            validate_purpose("kyc\nlockedUntil: 0"),
+            Err(RegistryError::InvalidPurpose)
+        );
+        assert_eq!(
+            validate_purpose(&"x".repeat(MAX_PURPOSE_LENGTH + 1)),
+            Err(RegistryError::InvalidPurpose)
+        );
+
+        let access = |field: &str| AccessScope {
+            mode: AccessMode::ReadOnly,
+            fields: vec![field.into()],
+        };
+        assert_eq!(access("name").validate(), Ok(()));
+        assert_eq!(
+            access("name,dob").validate(),
+            Err(RegistryError::InvalidAccessScope)
+        );
+        assert_eq!(
+            access("").validate(),
+            Err(RegistryError::InvalidAccessScope)
+        );
+    }
+
+    #[test]
+    fn grantee_profiles_need_a_name_and_an_https_url() {
+        assert_eq!(
+            validate_grantee_profile("Acme KYC", "https://acme.example"),
+            Ok(())
+        );
+
+        for (name, url) in [
+            ("", "https://acme.example"),
+            ("Acme\nKYC", "https://acme.example"),
+            ("Acme KYC", "http://acme.example"),
+            ("Acme KYC", "https://acme.example/ x"),
+        ] {
+            assert_eq!(
+                validate_grantee_profile(name, url),
+                Err(RegistryError::InvalidGranteeProfile)
+            );
+        }
+    }
+
+    #[test]
+    fn update_grantee_list_message_example() {
+        assert_eq!(
+            update_grantee_list_message(&owner(), GranteeList::Deny, &grantee(), true, 3),
+            "operation: updateGranteeList\n\
+            owner: ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ\n\
+            list: deny\n\
+            grantee: ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi\n\
+            listed: true\n\
+            revision: 3"
+        );
+    }
+
+    #[test]
+    fn set_allow_list_enabled_message_example() {
+        assert_eq!(
+            set_allow_list_enabled_message(&owner(), false, 4),
+            "operation: setAllowListEnabled\n\
+            owner: ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ\n\
+            enabled: false\n\
+            revision: 4"
+        );
+    }
+
+    #[test]
+    fn lock_policies_apply_to_grants_overlapping_their_prefix() {
+        let policy = LockPolicy {
+            data_id_prefix: "kyc/".into(),
+            min_lock_duration: 1,
+        };
+
+        assert!(policy.applies_to("kyc/passport", GrantScope::Exact));
+        assert!(!policy.applies_to("kyc", GrantScope::Exact));
+        assert!(!policy.applies_to("email", GrantScope::Exact));
+
+        assert!(policy.applies_to("kyc/", GrantScope::Prefix));
+        assert!(policy.applies_to("k", GrantScope::Prefix));
+        assert!(!policy.applies_to("email/", GrantScope::Prefix));
+    }
+
+    #[test]
+    fn renounce_grant_message_example() {
+        assert_eq!(
+            renounce_grant_message(&owner(), &grantee(), "A1", 2),
+            "operation: renounceGrant\n\
+            owner: ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ\n\
+            grantee: ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi\n\
+            dataId: A1\n\
+            revision: 2"
+        );
+    }
+
+    #[test]
+    fn revoke_all_message_example() {
+        assert_eq!(
+            revoke_all_message(&owner(), None, 1),
+            "operation: revokeAll\n\
+            owner: ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ\n\
+            grantee: *\n\
+            revision: 1"
+        );
+        assert!(revoke_all_message(&owner(), Some(&grantee()), 2).ends_with(
+            "grantee: ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi\n\
+            revision: 2"
+        ));
+    }
+
+    #[test]
+    fn nep413_hashed_payload_example() {
+        let payload = Nep413Payload {
+            message: "hello".into(),
+            nonce: [0; 32],
+            recipient: GRANT_MESSAGE_RECIPIENT.into(),
+            callback_url: None,
+        };
+
+        let mut expected = NEP413_TAG.to_le_bytes().to_vec();
+        expected.extend_from_slice(&5u32.to_le_bytes());
+        expected.extend_from_slice(b"hello");
+        expected.extend_from_slice(&[0; 32]);
+        expected.extend_from_slice(&12u32.to_le_bytes());
+        expected.extend_from_slice(b"idos.network");
+        expected.push(0);
+
+        assert_eq!(
+            nep413_hashed_payload(&payload),
+            <[u8; 32]>::from(Sha256::digest(&expected))
+        );
+    }
+}
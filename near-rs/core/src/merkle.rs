@@ -0,0 +1,185 @@
+// Checking the contract's grant proofs, and the hashing its sparse Merkle tree shares with them. Lives here so that
+// clients verify proofs with the same code the contract builds its tree with.
+
+use std::convert::TryInto;
+
+use near_sdk::borsh;
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::NearSchema;
+use sha2::{Digest, Sha256};
+
+use crate::Grant;
+
+pub type Hash = [u8; 32];
+
+pub const EMPTY_HASH: Hash = [0; 32];
+const LEAF_DOMAIN: u8 = 0;
+const NODE_DOMAIN: u8 = 1;
+
+#[derive(Serialize, Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MerkleLeaf {
+    pub key: String,
+    pub value_hash: String,
+}
+
+// `siblings` go from the root down. `leaf` is whatever the path for the grant id ends in: the grant's own leaf
+// (inclusion), some other grant's leaf sharing the same path prefix (exclusion), or nothing (exclusion).
+#[derive(Serialize, Deserialize, NearSchema, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct GrantProof {
+    pub leaf: Option<MerkleLeaf>,
+    pub siblings: Vec<String>,
+}
+
+fn hash_with_domain(domain: u8, a: &Hash, b: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([domain]);
+    hasher.update(a);
+    hasher.update(b);
+
+    hasher.finalize().into()
+}
+
+pub fn leaf_hash(key: &Hash, value_hash: &Hash) -> Hash {
+    hash_with_domain(LEAF_DOMAIN, key, value_hash)
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    hash_with_domain(NODE_DOMAIN, left, right)
+}
+
+pub fn grant_value_hash(grant: &Grant) -> Hash {
+    Sha256::digest(borsh::to_vec(grant).expect("Can't borsh encode grant")).into()
+}
+
+pub fn grant_key(grant_id: &str) -> Option<Hash> {
+    decode_hash(grant_id)
+}
+
+fn decode_hash(hex_hash: &str) -> Option<Hash> {
+    hex::decode(hex_hash).ok()?.try_into().ok()
+}
+
+pub fn bit_at(key: &Hash, depth: usize) -> u8 {
+    (key[depth / 8] >> (7 - depth % 8)) & 1
+}
+
+pub fn verify_grant_proof(
+    root: &str,
+    grant_id: &str,
+    grant: Option<&Grant>,
+    proof: &GrantProof,
+) -> bool {
+    let (Some(root), Some(key)) = (decode_hash(root), grant_key(grant_id)) else {
+        return false;
+    };
+
+    let Some(siblings) = proof
+        .siblings
+        .iter()
+        .map(|sibling| decode_hash(sibling))
+        .collect::<Option<Vec<Hash>>>()
+    else {
+        return false;
+    };
+
+    if siblings.len() > 256 {
+        return false;
+    }
+
+    let leaf = match &proof.leaf {
+        None => None,
+        Some(leaf) => match (decode_hash(&leaf.key), decode_hash(&leaf.value_hash)) {
+            (Some(leaf_key), Some(leaf_value_hash)) => Some((leaf_key, leaf_value_hash)),
+            _ => return false,
+        },
+    };
+
+    let mut hash = match (grant, leaf) {
+        (Some(grant), Some((leaf_key, leaf_value_hash))) => {
+            if leaf_key != key || leaf_value_hash != grant_value_hash(grant) {
+                return false;
+            }
+            leaf_hash(&leaf_key, &leaf_value_hash)
+        }
+        (Some(_), None) => return false,
+        (None, Some((leaf_key, leaf_value_hash))) => {
+            // Some other leaf can only prove absence if it sits exactly where ours would.
+            if leaf_key == key
+                || (0..siblings.len()).any(|d| bit_at(&leaf_key, d) != bit_at(&key, d))
+            {
+                return false;
+            }
+            leaf_hash(&leaf_key, &leaf_value_hash)
+        }
+        (None, None) => EMPTY_HASH,
+    };
+
+    for (depth, sibling) in siblings.iter().enumerate().rev() {
+        hash = match bit_at(&key, depth) {
+            0 => node_hash(&hash, sibling),
+            _ => node_hash(sibling, &hash),
+        };
+    }
+
+    hash == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{derive_grant_id, GrantScope};
+
+    fn grant() -> Grant {
+        Grant {
+            owner: "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+                .parse()
+                .unwrap(),
+            grantee: "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+                .parse()
+                .unwrap(),
+            data_id: "A1".into(),
+            scope: GrantScope::Exact,
+            locked_until: 0,
+            purpose: None,
+            access: None,
+            consent_hash: None,
+            valid_from: 0,
+        }
+    }
+
+    #[test]
+    fn a_lone_grant_is_the_root() {
+        let grant = grant();
+        let grant_id = derive_grant_id(&grant);
+        let key = grant_key(&grant_id).unwrap();
+        let value_hash = grant_value_hash(&grant);
+        let root = hex::encode(leaf_hash(&key, &value_hash));
+        let proof = GrantProof {
+            leaf: Some(MerkleLeaf {
+                key: grant_id.clone(),
+                value_hash: hex::encode(value_hash),
+            }),
+            siblings: vec![],
+        };
+
+        assert!(verify_grant_proof(&root, &grant_id, Some(&grant), &proof));
+        assert!(!verify_grant_proof(&root, &grant_id, None, &proof));
+        assert!(!verify_grant_proof(
+            &hex::encode(EMPTY_HASH),
+            &grant_id,
+            Some(&grant),
+            &proof
+        ));
+        assert!(verify_grant_proof(
+            &hex::encode(EMPTY_HASH),
+            &grant_id,
+            None,
+            &GrantProof {
+                leaf: None,
+                siblings: vec![],
+            }
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::Mutex;
+
+use anyhow::Context;
+use near_jsonrpc_client::{methods, JsonRpcClient};
+use near_jsonrpc_primitives::types::blocks::RpcBlockError;
+use near_jsonrpc_primitives::types::chunks::ChunkReference;
+use near_jsonrpc_primitives::types::light_client::RpcLightClientProofError;
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{
+    AccountId, BlockId, BlockReference, Finality, TransactionOrReceiptId,
+};
+use near_primitives::views::{BlockView, ExecutionOutcomeWithIdView, ExecutionStatusView};
+
+use access_grants_core::events::{decode_events, FractalRegistryEvent};
+
+use crate::store::Store;
+
+// Walks final blocks through RPC, feeding the events `contract_id` emitted into a `Store`.
+pub struct Follower {
+    rpc: JsonRpcClient,
+    contract_id: AccountId,
+}
+
+impl Follower {
+    pub fn new(rpc_url: &str, contract_id: AccountId) -> Self {
+        Self {
+            rpc: JsonRpcClient::connect(rpc_url),
+            contract_id,
+        }
+    }
+
+    pub async fn final_block(&self) -> anyhow::Result<BlockView> {
+        Ok(self
+            .rpc
+            .call(methods::block::RpcBlockRequest {
+                block_reference: BlockReference::Finality(Finality::Final),
+            })
+            .await?)
+    }
+
+    // Indexes every block after the store's cursor (or from `start_height`) up to `head`, the final block fetched
+    // for this batch, or up to the first one that isn't settled yet. The store is only locked for one block at a
+    // time, so queries keep being answered while we catch up.
+    pub async fn index_until(
+        &self,
+        store: &Mutex<Store>,
+        start_height: u64,
+        head: &BlockView,
+    ) -> anyhow::Result<()> {
+        let from_height = store
+            .lock()
+            .unwrap()
+            .cursor()?
+            .map_or(start_height, |cursor| cursor + 1);
+
+        for height in from_height..=head.header.height {
+            match self.events_at(height, head.header.hash).await? {
+                Some(events) => store.lock().unwrap().apply_block(height, &events)?,
+                // Some outcome isn't final yet: pick up from here next time.
+                None => break,
+            }
+        }
+
+        Ok(())
+    }
+
+    // `None` while some receipt that `height` brought in for the contract hasn't got a final outcome as of `head`.
+    async fn events_at(
+        &self,
+        height: u64,
+        head: CryptoHash,
+    ) -> anyhow::Result<Option<Vec<FractalRegistryEvent>>> {
+        match self.block(height).await? {
+            Some(block) => self.block_events(&block, head).await,
+            // Skipped heights have no block, and nothing to index.
+            None => Ok(Some(vec![])),
+        }
+    }
+
+    async fn block(&self, height: u64) -> anyhow::Result<Option<BlockView>> {
+        match self
+            .rpc
+            .call(methods::block::RpcBlockRequest {
+                block_reference: BlockReference::BlockId(BlockId::Height(height)),
+            })
+            .await
+        {
+            Ok(block) => Ok(Some(block)),
+            Err(err) => match err.handler_error() {
+                Some(RpcBlockError::UnknownBlock { .. }) => Ok(None),
+                _ => Err(err).with_context(|| format!("Can't fetch block {}", height)),
+            },
+        }
+    }
+
+    // Events from the receipts the contract executed in `block`, whoever sent them: transactions, other contracts or
+    // relayers. A chunk carries the receipts its block executes, except for local ones (transactions the contract
+    // account sends itself), which run right away. Receipts put off by congestion are still filed under the block
+    // that brought them in.
+    //
+    // Outcomes are looked up against the final `head`, so only final ones are found.
+    async fn block_events(
+        &self,
+        block: &BlockView,
+        head: CryptoHash,
+    ) -> anyhow::Result<Option<Vec<FractalRegistryEvent>>> {
+        let mut local_receipt_ids = vec![];
+        let mut receipt_ids = vec![];
+
+        // A chunk that was missed shows up again under its old height, and its receipts already ran.
+        for chunk_header in block
+            .chunks
+            .iter()
+            .filter(|chunk_header| chunk_header.height_included == block.header.height)
+        {
+            let chunk = self
+                .rpc
+                .call(methods::chunk::RpcChunkRequest {
+                    chunk_reference: ChunkReference::ChunkHash {
+                        chunk_id: chunk_header.chunk_hash,
+                    },
+                })
+                .await?;
+
+            for transaction in chunk.transactions.iter().filter(|transaction| {
+                transaction.signer_id == self.contract_id
+                    && transaction.receiver_id == self.contract_id
+            }) {
+                let outcome = self
+                    .outcome(
+                        TransactionOrReceiptId::Transaction {
+                            transaction_hash: transaction.hash,
+                            sender_id: transaction.signer_id.clone(),
+                        },
+                        head,
+                    )
+                    .await?;
+
+                match outcome {
+                    Some(outcome) => local_receipt_ids.extend(outcome.outcome.receipt_ids),
+                    None => return Ok(None),
+                }
+            }
+
+            receipt_ids.extend(
+                chunk
+                    .receipts
+                    .iter()
+                    .filter(|receipt| receipt.receiver_id == self.contract_id)
+                    .map(|receipt| receipt.receipt_id),
+            );
+        }
+
+        let mut events = vec![];
+
+        // Local receipts run before the incoming ones.
+        for receipt_id in local_receipt_ids.into_iter().chain(receipt_ids) {
+            let outcome = self
+                .outcome(
+                    TransactionOrReceiptId::Receipt {
+                        receipt_id,
+                        receiver_id: self.contract_id.clone(),
+                    },
+                    head,
+                )
+                .await?;
+            let Some(outcome) = outcome else {
+                return Ok(None);
+            };
+
+            // Failed receipts roll back their state changes, so their logs don't count.
+            if matches!(
+                outcome.outcome.status,
+                ExecutionStatusView::SuccessValue(_) | ExecutionStatusView::SuccessReceiptId(_)
+            ) {
+                events.extend(decode_events(
+                    outcome.outcome.logs.iter().map(String::as_str),
+                )?);
+            }
+        }
+
+        Ok(Some(events))
+    }
+
+    // The outcome of `id`, or `None` if it has none in a block before `head` yet.
+    async fn outcome(
+        &self,
+        id: TransactionOrReceiptId,
+        head: CryptoHash,
+    ) -> anyhow::Result<Option<ExecutionOutcomeWithIdView>> {
+        match self
+            .rpc
+            .call(
+                methods::light_client_proof::RpcLightClientExecutionProofRequest {
+                    id,
+                    light_client_head: head,
+                },
+            )
+            .await
+        {
+            Ok(proof) => Ok(Some(proof.outcome_proof)),
+            Err(err) => match err.handler_error() {
+                Some(RpcLightClientProofError::NotConfirmed { .. })
+                | Some(RpcLightClientProofError::UnknownTransactionOrReceipt { .. }) => Ok(None),
+                _ => Err(err).context("Can't fetch an execution outcome"),
+            },
+        }
+    }
+}
@@ -0,0 +1,6 @@
+pub mod follower;
+pub mod server;
+pub mod store;
+
+pub use follower::Follower;
+pub use store::Store;
@@ -0,0 +1,53 @@
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use clap::Parser;
+
+use access_grants_indexer::{server, Follower, Store};
+
+#[derive(Parser)]
+#[command(
+    name = "access-grants-indexer",
+    about = "Index FractalRegistry events into SQLite and serve them over HTTP"
+)]
+struct Cli {
+    #[arg(long, default_value = "http://localhost:3030")]
+    rpc_url: String,
+    #[arg(long)]
+    contract_id: String,
+    /// SQLite database to keep grants in. Indexing resumes from where it stopped.
+    #[arg(long, default_value = "grants.sqlite")]
+    db: PathBuf,
+    /// First block to index when the database is empty, usually the contract's deployment block.
+    #[arg(long, default_value_t = 0)]
+    start_height: u64,
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+    /// How long to wait for new final blocks once caught up.
+    #[arg(long, default_value_t = 1000)]
+    poll_interval_ms: u64,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let follower = Follower::new(&cli.rpc_url, cli.contract_id.parse()?);
+    let store = Arc::new(Mutex::new(Store::open(&cli.db)?));
+    let server = tokio::spawn(server::serve(cli.listen, store.clone()));
+
+    loop {
+        if server.is_finished() {
+            return server.await?;
+        }
+
+        let head = follower.final_block().await?;
+        follower
+            .index_until(&store, cli.start_height, &head)
+            .await?;
+
+        tokio::time::sleep(Duration::from_millis(cli.poll_interval_ms)).await;
+    }
+}
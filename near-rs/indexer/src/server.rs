@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+
+use access_grants_core::{AccessMode, PublicKey};
+
+use crate::store::Store;
+
+// Serves `GET /grants?owner=&grantee=&data_id=&purpose=&access_mode=`, answering like the contract's `find_grants`.
+pub async fn serve(addr: SocketAddr, store: Arc<Mutex<Store>>) -> anyhow::Result<()> {
+    let make_service = make_service_fn(move |_| {
+        let store = store.clone();
+
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let store = store.clone();
+                async move { Ok::<_, Infallible>(handle(&store, request)) }
+            }))
+        }
+    });
+
+    Server::bind(&addr).serve(make_service).await?;
+
+    Ok(())
+}
+
+fn handle(store: &Mutex<Store>, request: Request<Body>) -> Response<Body> {
+    if request.method() != Method::GET || request.uri().path() != "/grants" {
+        return respond(StatusCode::NOT_FOUND, "Not found".into());
+    }
+
+    let query: HashMap<&str, String> = request
+        .uri()
+        .query()
+        .unwrap_or("")
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(name, value)| (name, percent_decode(value)))
+        .collect();
+
+    let parse_key = |name: &str| -> Result<Option<PublicKey>, String> {
+        query
+            .get(name)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| format!("Invalid {}: {:?}", name, value))
+            })
+            .transpose()
+    };
+
+    let (owner, grantee) = match (parse_key("owner"), parse_key("grantee")) {
+        (Ok(owner), Ok(grantee)) => (owner, grantee),
+        (Err(err), _) | (_, Err(err)) => return respond(StatusCode::BAD_REQUEST, err),
+    };
+
+    let access_mode = match query.get("access_mode").map(String::as_str) {
+        None => None,
+        Some("read_only") => Some(AccessMode::ReadOnly),
+        Some("read_write") => Some(AccessMode::ReadWrite),
+        Some(value) => {
+            return respond(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid access_mode: {:?}", value),
+            )
+        }
+    };
+
+    let grants = store.lock().unwrap().find_grants(
+        owner.as_ref(),
+        grantee.as_ref(),
+        query.get("data_id").map(String::as_str),
+        query.get("purpose").map(String::as_str),
+        access_mode,
+    );
+
+    match grants {
+        Ok(grants) => Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&grants).unwrap().into())
+            .unwrap(),
+        Err(err) => respond(StatusCode::BAD_REQUEST, err.to_string()),
+    }
+}
+
+fn respond(status: StatusCode, message: String) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(message.into())
+        .unwrap()
+}
+
+fn percent_decode(value: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = value.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        match (byte, tail) {
+            (b'%', [high, low, tail @ ..]) => {
+                match std::str::from_utf8(&[*high, *low])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(decoded) => {
+                        bytes.push(decoded);
+                        rest = tail;
+                        continue;
+                    }
+                    None => bytes.push(byte),
+                }
+            }
+            (b'+', _) => bytes.push(b' '),
+            _ => bytes.push(byte),
+        }
+        rest = tail;
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+#[test]
+fn percent_decode_example() {
+    assert_eq!(percent_decode("ed25519%3Aabc"), "ed25519:abc");
+    assert_eq!(percent_decode("some+data%2F1"), "some data/1");
+    assert_eq!(percent_decode("100%"), "100%");
+}
@@ -0,0 +1,504 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use access_grants_core::events::{
+    FractalRegistryEvent, GrantEventV1, GrantEventV4, GrantLockExtendedV0,
+};
+use access_grants_core::{AccessMode, Grant, GrantScope, PublicKey, RegistryError};
+
+// Grants as of the last indexed block, mirroring what the contract would answer to `find_grants`.
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> anyhow::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> anyhow::Result<Self> {
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS grants (
+                owner TEXT NOT NULL,
+                grantee TEXT NOT NULL,
+                data_id TEXT NOT NULL,
+                scope TEXT NOT NULL,
+                locked_until INTEGER NOT NULL,
+                purpose TEXT,
+                access TEXT,
+                consent_hash TEXT,
+                valid_from INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (owner, grantee, data_id, scope, locked_until)
+            );
+            CREATE INDEX IF NOT EXISTS grants_by_grantee ON grants (grantee, data_id);
+            CREATE TABLE IF NOT EXISTS cursor (
+                id INTEGER PRIMARY KEY CHECK (id = 0),
+                block_height INTEGER NOT NULL
+            );",
+        )?;
+
+        Ok(Self { conn })
+    }
+
+    // Height of the last fully indexed block, if any.
+    pub fn cursor(&self) -> anyhow::Result<Option<u64>> {
+        Ok(self
+            .conn
+            .query_row("SELECT block_height FROM cursor WHERE id = 0", [], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    // Applies the events of one block and moves the cursor past it, atomically.
+    pub fn apply_block(
+        &mut self,
+        block_height: u64,
+        events: &[FractalRegistryEvent],
+    ) -> anyhow::Result<()> {
+        let tx = self.conn.transaction()?;
+
+        for event in events {
+            match event {
+                FractalRegistryEvent::GrantInserted(event) => {
+                    insert_grant(&tx, event.clone().into_latest())?
+                }
+                FractalRegistryEvent::GrantDeleted(event) => {
+                    delete_grants(&tx, event.clone().into_latest())?
+                }
+                FractalRegistryEvent::GrantRenounced(event) => {
+                    delete_grants(&tx, event.clone().into_latest())?
+                }
+                FractalRegistryEvent::GrantLockExtended(event) => {
+                    extend_lock(&tx, event.clone().into_latest())?
+                }
+                // We don't serve keys, only grants.
+                FractalRegistryEvent::GrantKeySet(_)
+                | FractalRegistryEvent::EncryptionKeyPublished(_) => {}
+                // Nor profiles: wallets read those from the contract.
+                FractalRegistryEvent::GranteeProfileRegistered(_)
+                | FractalRegistryEvent::GranteeVerified(_) => {}
+                // Lists and lock policies only gate future inserts.
+                FractalRegistryEvent::GranteeListUpdated(_)
+                | FractalRegistryEvent::AllowListEnabled(_)
+                | FractalRegistryEvent::LockPoliciesSet(_) => {}
+                // Requests don't change any grant. An approval comes with its own `grant_inserted`.
+                FractalRegistryEvent::AccessRequested(_)
+                | FractalRegistryEvent::AccessRequestApproved(_)
+                | FractalRegistryEvent::AccessRequestRejected(_) => {}
+            }
+        }
+
+        tx.execute(
+            "INSERT OR REPLACE INTO cursor (id, block_height) VALUES (0, ?1)",
+            params![block_height],
+        )?;
+
+        Ok(tx.commit()?)
+    }
+
+    pub fn find_grants(
+        &self,
+        owner: Option<&PublicKey>,
+        grantee: Option<&PublicKey>,
+        data_id: Option<&str>,
+        purpose: Option<&str>,
+        access_mode: Option<AccessMode>,
+    ) -> anyhow::Result<Vec<Grant>> {
+        if owner.is_none() && grantee.is_none() {
+            return Err(RegistryError::MissingOwnerOrGrantee.into());
+        }
+
+        // A `data_id` matches exact grants for it, and prefix grants for any prefix of it.
+        let mut statement = self.conn.prepare(
+            "SELECT owner, grantee, data_id, scope, locked_until, purpose, access,
+                consent_hash, valid_from
+            FROM grants
+            WHERE (?1 IS NULL OR owner = ?1)
+            AND (?2 IS NULL OR grantee = ?2)
+            AND (
+                ?3 IS NULL
+                OR (scope = 'exact' AND data_id = ?3)
+                OR (scope = 'prefix' AND substr(?3, 1, length(data_id)) = data_id)
+            )
+            AND (?4 IS NULL OR purpose = ?4)
+            ORDER BY owner, grantee, data_id, scope, locked_until",
+        )?;
+
+        let rows = statement.query_map(
+            params![
+                owner.map(String::from),
+                grantee.map(String::from),
+                data_id,
+                purpose
+            ],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get(4)?,
+                    row.get(5)?,
+                    row.get::<_, Option<String>>(6)?,
+                    row.get::<_, Option<String>>(7)?,
+                    row.get(8)?,
+                ))
+            },
+        )?;
+
+        let grants = rows
+            .map(|row| {
+                let (
+                    owner,
+                    grantee,
+                    data_id,
+                    scope,
+                    locked_until,
+                    purpose,
+                    access,
+                    consent_hash,
+                    valid_from,
+                ) = row?;
+
+                Ok(Grant {
+                    owner: owner.parse()?,
+                    grantee: grantee.parse()?,
+                    data_id,
+                    scope: match scope.as_str() {
+                        "prefix" => GrantScope::Prefix,
+                        _ => GrantScope::Exact,
+                    },
+                    locked_until,
+                    purpose,
+                    access: access
+                        .map(|access| serde_json::from_str(&access))
+                        .transpose()?,
+                    consent_hash: consent_hash
+                        .map(|consent_hash| serde_json::from_str(&consent_hash))
+                        .transpose()?,
+                    valid_from,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(grants
+            .into_iter()
+            .filter(|grant| access_mode.map_or(true, |mode| grant.allows(mode)))
+            .collect())
+    }
+}
+
+// `access` and `consent_hash` are kept as JSON, since nothing queries into them.
+fn insert_grant(tx: &Transaction, grant: GrantEventV4) -> anyhow::Result<()> {
+    tx.execute(
+        "INSERT OR REPLACE INTO grants
+            (owner, grantee, data_id, scope, locked_until, purpose, access, consent_hash,
+                valid_from)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            String::from(&grant.owner),
+            String::from(&grant.grantee),
+            grant.data_id,
+            scope_name(grant.scope),
+            grant.locked_until,
+            grant.purpose,
+            grant
+                .access
+                .map(|access| serde_json::to_string(&access))
+                .transpose()?,
+            grant
+                .consent_hash
+                .map(|consent_hash| serde_json::to_string(&consent_hash))
+                .transpose()?,
+            grant.valid_from,
+        ],
+    )?;
+
+    Ok(())
+}
+
+// Like `delete_grant`, a `locked_until` of 0 stands for every matching grant.
+fn delete_grants(tx: &Transaction, grant: GrantEventV1) -> anyhow::Result<()> {
+    tx.execute(
+        "DELETE FROM grants
+        WHERE owner = ?1 AND grantee = ?2 AND data_id = ?3 AND scope = ?4
+        AND (?5 = 0 OR locked_until = ?5)",
+        params![
+            String::from(&grant.owner),
+            String::from(&grant.grantee),
+            grant.data_id,
+            scope_name(grant.scope),
+            grant.locked_until,
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn extend_lock(tx: &Transaction, event: GrantLockExtendedV0) -> anyhow::Result<()> {
+    tx.execute(
+        "UPDATE grants SET locked_until = ?6
+        WHERE owner = ?1 AND grantee = ?2 AND data_id = ?3 AND scope = ?4 AND locked_until = ?5",
+        params![
+            String::from(&event.owner),
+            String::from(&event.grantee),
+            event.data_id,
+            scope_name(event.scope),
+            event.previous_locked_until,
+            event.locked_until,
+        ],
+    )?;
+
+    Ok(())
+}
+
+fn scope_name(scope: GrantScope) -> &'static str {
+    match scope {
+        GrantScope::Exact => "exact",
+        GrantScope::Prefix => "prefix",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use access_grants_core::events::{
+        GrantDeleted, GrantEventV0, GrantEventV2, GrantInserted, GrantLockExtended,
+    };
+    use access_grants_core::AccessScope;
+
+    fn event_data(data_id: &str, locked_until: u64) -> GrantEventV0 {
+        GrantEventV0 {
+            owner: "ed25519:BCUg4havhRURACQAFK48e6ScqcJgPbeqHbfcmNoWp3fZ"
+                .parse()
+                .unwrap(),
+            grantee: "ed25519:mrjrfx8wSA9pYyMEeMm2QnFe9ct1P8CRkmU55h8MxEi"
+                .parse()
+                .unwrap(),
+            data_id: data_id.into(),
+            locked_until,
+        }
+    }
+
+    #[test]
+    fn apply_inserts_and_deletes() {
+        let mut store = Store::open_in_memory().unwrap();
+        let owner = event_data("A1", 0).owner;
+
+        store
+            .apply_block(
+                10,
+                &[
+                    FractalRegistryEvent::GrantInserted(GrantInserted::V0(event_data("A1", 1))),
+                    FractalRegistryEvent::GrantInserted(GrantInserted::V0(event_data("A1", 2))),
+                    FractalRegistryEvent::GrantInserted(GrantInserted::V0(event_data("A2", 0))),
+                ],
+            )
+            .unwrap();
+        assert_eq!(store.cursor().unwrap(), Some(10));
+        assert_eq!(
+            store
+                .find_grants(Some(&owner), None, None, None, None)
+                .unwrap()
+                .len(),
+            3
+        );
+
+        store
+            .apply_block(
+                11,
+                &[FractalRegistryEvent::GrantDeleted(GrantDeleted::V0(
+                    event_data("A1", 1),
+                ))],
+            )
+            .unwrap();
+        assert_eq!(
+            store
+                .find_grants(Some(&owner), None, Some("A1"), None, None)
+                .unwrap(),
+            vec![Grant {
+                owner: owner.clone(),
+                grantee: event_data("A1", 2).grantee,
+                data_id: "A1".into(),
+                scope: GrantScope::Exact,
+                locked_until: 2,
+                purpose: None,
+                access: None,
+                consent_hash: None,
+                valid_from: 0,
+            }]
+        );
+
+        store
+            .apply_block(
+                12,
+                &[FractalRegistryEvent::GrantDeleted(GrantDeleted::V0(
+                    event_data("A1", 0),
+                ))],
+            )
+            .unwrap();
+        assert_eq!(
+            store
+                .find_grants(Some(&owner), None, Some("A1"), None, None)
+                .unwrap(),
+            vec![]
+        );
+        assert!(store
+            .find_grants(None, None, Some("A2"), None, None)
+            .is_err());
+    }
+
+    #[test]
+    fn apply_lock_extensions() {
+        let mut store = Store::open_in_memory().unwrap();
+        let grant = event_data("A1", 1);
+        let owner = grant.owner.clone();
+
+        store
+            .apply_block(
+                10,
+                &[
+                    FractalRegistryEvent::GrantInserted(GrantInserted::V0(grant.clone())),
+                    FractalRegistryEvent::GrantLockExtended(GrantLockExtended::V0(
+                        GrantLockExtendedV0 {
+                            grant_id: "some id".into(),
+                            owner: grant.owner,
+                            grantee: grant.grantee,
+                            data_id: grant.data_id,
+                            scope: GrantScope::Exact,
+                            previous_locked_until: 1,
+                            locked_until: 5,
+                        },
+                    )),
+                ],
+            )
+            .unwrap();
+
+        let grants = store
+            .find_grants(Some(&owner), None, None, None, None)
+            .unwrap();
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].locked_until, 5);
+    }
+
+    #[test]
+    fn prefix_grants_match_data_ids_under_them() {
+        let mut store = Store::open_in_memory().unwrap();
+        let prefix_grant = GrantEventV1 {
+            data_id: "creds/".into(),
+            scope: GrantScope::Prefix,
+            ..event_data("", 0).into()
+        };
+        let owner = prefix_grant.owner.clone();
+
+        store
+            .apply_block(
+                10,
+                &[FractalRegistryEvent::GrantInserted(GrantInserted::V1(
+                    prefix_grant.clone(),
+                ))],
+            )
+            .unwrap();
+        assert_eq!(
+            store
+                .find_grants(Some(&owner), None, Some("creds/passport"), None, None)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(
+            store
+                .find_grants(Some(&owner), None, Some("creds"), None, None)
+                .unwrap(),
+            vec![]
+        );
+
+        // Deleting an exact grant with the same data id leaves the prefix grant alone.
+        store
+            .apply_block(
+                11,
+                &[FractalRegistryEvent::GrantDeleted(GrantDeleted::V0(
+                    event_data("creds/", 0),
+                ))],
+            )
+            .unwrap();
+        assert_eq!(
+            store
+                .find_grants(Some(&owner), None, Some("creds/passport"), None, None)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        store
+            .apply_block(
+                12,
+                &[FractalRegistryEvent::GrantDeleted(GrantDeleted::V1(
+                    prefix_grant,
+                ))],
+            )
+            .unwrap();
+        assert_eq!(
+            store
+                .find_grants(Some(&owner), None, Some("creds/passport"), None, None)
+                .unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn filter_by_purpose_and_access_mode() {
+        let mut store = Store::open_in_memory().unwrap();
+        let grant = |data_id: &str, purpose: &str, mode| GrantEventV2 {
+            purpose: Some(purpose.into()),
+            access: Some(AccessScope {
+                mode,
+                fields: vec!["name".into()],
+            }),
+            ..GrantEventV1::from(event_data(data_id, 0)).into()
+        };
+        let owner = event_data("", 0).owner;
+
+        store
+            .apply_block(
+                10,
+                &[
+                    FractalRegistryEvent::GrantInserted(GrantInserted::V2(grant(
+                        "A1",
+                        "kyc",
+                        AccessMode::ReadOnly,
+                    ))),
+                    FractalRegistryEvent::GrantInserted(GrantInserted::V2(grant(
+                        "A2",
+                        "audit",
+                        AccessMode::ReadWrite,
+                    ))),
+                ],
+            )
+            .unwrap();
+
+        let grants = store
+            .find_grants(Some(&owner), None, None, Some("kyc"), None)
+            .unwrap();
+        assert_eq!(grants.len(), 1);
+        assert_eq!(
+            grants[0].access,
+            grant("A1", "kyc", AccessMode::ReadOnly).access
+        );
+        assert_eq!(
+            store
+                .find_grants(Some(&owner), None, None, None, Some(AccessMode::ReadWrite))
+                .unwrap()
+                .iter()
+                .map(|grant| grant.data_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["A2"]
+        );
+    }
+}
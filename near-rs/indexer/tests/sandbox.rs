@@ -0,0 +1,57 @@
+use std::sync::Mutex;
+use std::{env, fs};
+
+use lazy_static::lazy_static;
+use near_workspaces::types::{KeyType, SecretKey};
+
+use access_grants_client::RegistryClient;
+use access_grants_indexer::{Follower, Store};
+
+lazy_static! {
+    static ref WASM: Vec<u8> = {
+        let wasm_arg: String = env::var("CONTRACT_LOCATION").unwrap_or(
+            "../contract/target/wasm32-unknown-unknown/release/access_grants.wasm".into(),
+        );
+        let wasm_filepath = fs::canonicalize(env::current_dir().unwrap().join(wasm_arg)).unwrap();
+        std::fs::read(wasm_filepath).unwrap()
+    };
+}
+
+#[tokio::test]
+async fn index_inserts_and_deletes() -> anyhow::Result<()> {
+    let worker = near_workspaces::sandbox().await?;
+    let contract = worker.dev_deploy(&WASM).await?;
+    let account = worker.dev_create_account().await?;
+    let client = RegistryClient::new(account, contract.id().clone());
+
+    let grantee = SecretKey::from_random(KeyType::ED25519).public_key();
+    client
+        .insert_grant(&grantee, "A1", None, None, None, None, None)
+        .await?;
+    client
+        .insert_grant(&grantee, "A2", None, None, None, None, None)
+        .await?;
+    client.delete_grant(&grantee, "A1", None).await?;
+
+    // Blocks only get indexed once their outcomes are final.
+    worker.fast_forward(5).await?;
+
+    let follower = Follower::new(&worker.rpc_addr(), contract.id().as_str().parse()?);
+    let store = Mutex::new(Store::open_in_memory()?);
+    let head = follower.final_block().await?;
+    follower.index_until(&store, 0, &head).await?;
+    let store = store.into_inner().unwrap();
+
+    let owner = client
+        .account()
+        .secret_key()
+        .public_key()
+        .to_string()
+        .parse()?;
+    let grants = store.find_grants(Some(&owner), None, None, None, None)?;
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].data_id, "A2");
+    assert_eq!(store.cursor()?, Some(head.header.height));
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+const CONTRACT_DIR: &str = "../contract";
+const GENERATED_ABI: &str = "../contract/target/near/access_grants_abi.json";
+const ABI_SNAPSHOT: &str = "../contract/abi/access_grants.json";
+
+// Only the body (functions and types) is compared: the metadata has toolchain versions and build details in it.
+// After a deliberate interface change, rerun with `UPDATE_ABI_SNAPSHOT=1` and commit the new snapshot.
+#[test]
+fn abi_matches_snapshot() -> anyhow::Result<()> {
+    let status = Command::new("cargo")
+        .args(["near", "abi"])
+        .current_dir(CONTRACT_DIR)
+        .status()?;
+    assert!(
+        status.success(),
+        "`cargo near abi` failed; is cargo-near installed?"
+    );
+
+    let generated: serde_json::Value = serde_json::from_slice(&fs::read(GENERATED_ABI)?)?;
+    let body = &generated["body"];
+
+    if env::var_os("UPDATE_ABI_SNAPSHOT").is_some() {
+        fs::write(ABI_SNAPSHOT, serde_json::to_string_pretty(body)? + "\n")?;
+    }
+
+    let snapshot: serde_json::Value = serde_json::from_slice(&fs::read(ABI_SNAPSHOT)?)?;
+    assert!(
+        *body == snapshot,
+        "The contract's ABI no longer matches {}. If that's intended, rerun with UPDATE_ABI_SNAPSHOT=1.",
+        ABI_SNAPSHOT
+    );
+
+    Ok(())
+}
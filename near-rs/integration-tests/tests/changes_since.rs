@@ -0,0 +1,82 @@
+use serde_json::json;
+
+mod helpers;
+use helpers::{create_public_key, scenario_base, ChangesPage};
+
+mod assert;
+
+#[tokio::test]
+async fn inserts_and_deletes_are_sequenced() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let bob = create_public_key();
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({"grantee": bob, "data_id": "A1"}))
+            .transact()
+            .await?,
+    );
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({"grantee": bob, "data_id": "A2"}))
+            .transact()
+            .await?,
+    );
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "delete_grant")
+            .args_json(json!({"grantee": bob, "data_id": "A1"}))
+            .transact()
+            .await?,
+    );
+
+    let page = test_account
+        .call(contract.id(), "changes_since")
+        .args_json(json!({ "seq": 0 }))
+        .view()
+        .await?
+        .json::<ChangesPage>()
+        .unwrap();
+
+    assert_eq!(page.oldest_seq, 1);
+    assert_eq!(page.latest_seq, 3);
+    assert_eq!(
+        page.changes
+            .iter()
+            .map(|c| (c.seq, c.kind.as_str(), c.grant.data_id.as_str()))
+            .collect::<Vec<_>>(),
+        vec![(1, "inserted", "A1"), (2, "inserted", "A2"), (3, "deleted", "A1")]
+    );
+    assert!(page
+        .changes
+        .windows(2)
+        .all(|w| w[0].block_height <= w[1].block_height));
+
+    let page = test_account
+        .call(contract.id(), "changes_since")
+        .args_json(json!({"seq": 1, "limit": 1}))
+        .view()
+        .await?
+        .json::<ChangesPage>()
+        .unwrap();
+    assert_eq!(
+        page.changes.iter().map(|c| c.seq).collect::<Vec<_>>(),
+        vec![2]
+    );
+
+    assert_eq!(
+        test_account
+            .call(contract.id(), "latest_change_seq")
+            .args_json(json!({}))
+            .view()
+            .await?
+            .json::<u64>()
+            .unwrap(),
+        3
+    );
+
+    Ok(())
+}
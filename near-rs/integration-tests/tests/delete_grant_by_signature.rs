@@ -1,11 +1,13 @@
+use access_grants_core::RegistryError;
 use serde_json::json;
 
 mod helpers;
-use helpers::{create_public_key, scenario_base, Grant};
+use helpers::{create_public_key, panicked_with, scenario_base, Grant};
 
 mod assert;
 
 mod nep413;
+use nep413::signed_args;
 
 #[tokio::test]
 async fn happy_path() -> anyhow::Result<()> {
@@ -21,7 +23,6 @@ async fn happy_path() -> anyhow::Result<()> {
     let grantee = create_public_key();
     let data_id: String = "DATA_ID".into();
     let locked_until = 0;
-    let nonce = nep413::generate_nonce();
 
     assert::transaction_success(
         owner_account
@@ -31,46 +32,23 @@ async fn happy_path() -> anyhow::Result<()> {
             .await?,
     );
 
-    let recipient = some_other_account
-        .call(contract.id(), "grant_message_recipient")
-        .args_json(json!({}))
-        .view()
-        .await?
-        .json::<String>()
-        .unwrap();
-
-    let message = some_other_account
-        .call(contract.id(), "delete_grant_by_signature_message")
-        .args_json(json!({
+    let args = signed_args(
+        &some_other_account,
+        &contract,
+        "delete_grant_by_signature_message",
+        json!({
             "owner": owner,
             "grantee": grantee,
             "data_id": data_id,
             "locked_until": locked_until,
-        }))
-        .view()
-        .await?
-        .json::<String>()
-        .unwrap();
-
-    let signature = nep413::Payload {
-        message,
-        nonce,
-        recipient,
-        callback_url: None,
-    }
-    .sign_with(owner_sk);
-
+        }),
+        &owner_sk,
+    )
+    .await?;
     assert::transaction_success(
         some_other_account
             .call(contract.id(), "delete_grant_by_signature")
-            .args_json(json!({
-                "owner": owner,
-                "grantee": grantee,
-                "data_id": data_id,
-                "locked_until": locked_until,
-                "nonce": nonce,
-                "signature": signature,
-            }))
+            .args_json(args)
             .transact()
             .await?,
     );
@@ -112,7 +90,7 @@ async fn wrong_signature() -> anyhow::Result<()> {
             }))
             .transact()
             .await?,
-        r#"Action #0: ExecutionError("Smart contract panicked: Signature doesn't match")"#,
+        &panicked_with(RegistryError::InvalidSignature),
     );
 
     Ok(())
@@ -0,0 +1,76 @@
+use access_grants_core::{EncryptionKey, RegistryError};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{create_secret_key, extract_public_key, panicked_with, scenario_base};
+
+mod assert;
+
+mod nep413;
+use nep413::signed_args;
+
+#[tokio::test]
+async fn grantee_publishes_and_rotates_encryption_key() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let grantee_sk = create_secret_key();
+    let grantee = extract_public_key(&grantee_sk);
+
+    let publish = |args: Value| {
+        test_account
+            .call(contract.id(), "publish_encryption_key_by_signature")
+            .args_json(args)
+            .transact()
+    };
+
+    let publish_args = |encryption_key: EncryptionKey, rotation: u32| {
+        signed_args(
+            &test_account,
+            &contract,
+            "publish_encryption_key_by_signature_message",
+            json!({
+                "grantee": grantee,
+                "encryption_key": encryption_key,
+                "rotation": rotation,
+            }),
+            &grantee_sk,
+        )
+    };
+
+    let first = publish_args(EncryptionKey([1; 32]), 1).await?;
+    let second = publish_args(EncryptionKey([2; 32]), 2).await?;
+
+    assert::transaction_success(publish(first.clone()).await?);
+    assert::transaction_success(publish(second).await?);
+
+    // Replaying the first key can't roll the grantee back to it.
+    assert::transaction_failure(
+        publish(first).await?,
+        &panicked_with(RegistryError::InvalidRotation),
+    );
+
+    let current = test_account
+        .call(contract.id(), "grantee_encryption_key")
+        .args_json(json!({ "grantee": grantee }))
+        .view()
+        .await?
+        .json::<Value>()?;
+    assert_eq!(current["encryption_key"], json!(EncryptionKey([2; 32])));
+    assert_eq!(current["rotation"], 2);
+
+    let history = test_account
+        .call(contract.id(), "grantee_encryption_key_history")
+        .args_json(json!({ "grantee": grantee }))
+        .view()
+        .await?
+        .json::<Vec<Value>>()?;
+    assert_eq!(
+        history
+            .iter()
+            .map(|key| key["rotation"].clone())
+            .collect::<Vec<_>>(),
+        vec![json!(1), json!(2)]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use access_grants_core::{derive_grant_id, Grant, RegistryError};
+use serde_json::json;
+
+mod helpers;
+use helpers::{
+    create_public_key, create_secret_key, extract_public_key, panicked_with, scenario_base,
+};
+
+mod assert;
+
+mod nep413;
+use nep413::signed_args;
+
+const IN_THE_FUTURE: u64 = u64::MAX / 2;
+
+#[tokio::test]
+async fn relayed_extension_keeps_the_grant_id() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner_sk = create_secret_key();
+    let owner = extract_public_key(&owner_sk);
+    let grantee = create_public_key();
+
+    let insert_args = signed_args(
+        &test_account,
+        &contract,
+        "insert_grant_by_signature_message",
+        json!({
+            "owner": owner,
+            "grantee": grantee,
+            "data_id": "A1",
+            "locked_until": IN_THE_FUTURE,
+        }),
+        &owner_sk,
+    )
+    .await?;
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant_by_signature")
+            .args_json(insert_args)
+            .transact()
+            .await?,
+    );
+
+    let find_grants = || async {
+        test_account
+            .call(contract.id(), "find_grants")
+            .args_json(json!({ "owner": owner }))
+            .view()
+            .await?
+            .json::<Vec<Grant>>()
+    };
+    let grant_id = derive_grant_id(&find_grants().await?[0]);
+
+    let (test_account, contract, owner_sk) = (&test_account, &contract, &owner_sk);
+    let extend_lock = |new_locked_until: u64, revision: u32| {
+        let owner = owner.clone();
+        let grant_id = grant_id.clone();
+        async move {
+            let args = signed_args(
+                test_account,
+                contract,
+                "extend_lock_by_signature_message",
+                json!({
+                    "owner": owner,
+                    "grant_id": grant_id,
+                    "new_locked_until": new_locked_until,
+                    "revision": revision,
+                }),
+                owner_sk,
+            )
+            .await?;
+
+            anyhow::Ok(
+                test_account
+                    .call(contract.id(), "extend_lock_by_signature")
+                    .args_json(args)
+                    .transact()
+                    .await?,
+            )
+        }
+    };
+
+    assert::transaction_success(extend_lock(IN_THE_FUTURE + 1, 1).await?);
+    assert::transaction_failure(
+        extend_lock(IN_THE_FUTURE, 2).await?,
+        &panicked_with(RegistryError::LockNotExtended),
+    );
+    assert::transaction_failure(
+        extend_lock(IN_THE_FUTURE + 2, 1).await?,
+        &panicked_with(RegistryError::InvalidLockRevision),
+    );
+
+    let grants = find_grants().await?;
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].locked_until, IN_THE_FUTURE + 1);
+    assert_eq!(derive_grant_id(&grants[0]), grant_id);
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use serde_json::json;
+
+mod helpers;
+use helpers::{create_public_key, scenario_base, Grant, GrantsForKey};
+
+mod assert;
+
+#[tokio::test]
+async fn grouped_by_grantee_and_data_id() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner = test_account.secret_key().public_key().to_string();
+    let bob = create_public_key();
+    let charlie = create_public_key();
+
+    for (grantee, data_id) in [(&bob, "A1"), (&bob, "A2"), (&charlie, "A2")] {
+        assert::transaction_success(
+            test_account
+                .call(contract.id(), "insert_grant")
+                .args_json(json!({"grantee": grantee, "data_id": data_id}))
+                .transact()
+                .await?,
+        );
+    }
+
+    let results = test_account
+        .call(contract.id(), "find_grants_multi")
+        .args_json(json!({
+            "grantees": [bob, charlie],
+            "data_ids": ["A1", "A2"],
+        }))
+        .view()
+        .await?
+        .json::<Vec<GrantsForKey>>()
+        .unwrap();
+
+    let grant = |grantee: &String, data_id: &str| Grant {
+        owner: owner.clone(),
+        grantee: grantee.clone(),
+        data_id: data_id.into(),
+        locked_until: 0,
+    };
+
+    assert_eq!(
+        results,
+        vec![
+            GrantsForKey {
+                grantee: Some(bob.clone()),
+                data_id: Some("A1".into()),
+                grants: vec![grant(&bob, "A1")],
+            },
+            GrantsForKey {
+                grantee: Some(bob.clone()),
+                data_id: Some("A2".into()),
+                grants: vec![grant(&bob, "A2")],
+            },
+            GrantsForKey {
+                grantee: Some(charlie.clone()),
+                data_id: Some("A1".into()),
+                grants: vec![],
+            },
+            GrantsForKey {
+                grantee: Some(charlie.clone()),
+                data_id: Some("A2".into()),
+                grants: vec![grant(&charlie, "A2")],
+            },
+        ]
+    );
+
+    Ok(())
+}
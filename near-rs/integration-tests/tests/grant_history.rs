@@ -0,0 +1,113 @@
+use serde_json::json;
+
+mod helpers;
+use helpers::{create_public_key, scenario_base, Grant, GrantTombstone};
+
+mod assert;
+
+#[tokio::test]
+async fn deletions_leave_tombstones() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner = test_account.secret_key().public_key().to_string();
+    let bob = create_public_key();
+    let charlie = create_public_key();
+
+    for (grantee, data_id) in [(&bob, "A1"), (&bob, "A2"), (&charlie, "A1")] {
+        assert::transaction_success(
+            test_account
+                .call(contract.id(), "insert_grant")
+                .args_json(json!({"grantee": grantee, "data_id": data_id}))
+                .transact()
+                .await?,
+        );
+    }
+
+    assert_eq!(
+        test_account
+            .call(contract.id(), "grant_history")
+            .args_json(json!({ "owner": owner }))
+            .view()
+            .await?
+            .json::<Vec<GrantTombstone>>()
+            .unwrap(),
+        vec![]
+    );
+
+    for (grantee, data_id) in [(&bob, "A1"), (&charlie, "A1"), (&bob, "A2")] {
+        assert::transaction_success(
+            test_account
+                .call(contract.id(), "delete_grant")
+                .args_json(json!({"grantee": grantee, "data_id": data_id}))
+                .transact()
+                .await?,
+        );
+    }
+
+    let history = test_account
+        .call(contract.id(), "grant_history")
+        .args_json(json!({ "owner": owner }))
+        .view()
+        .await?
+        .json::<Vec<GrantTombstone>>()
+        .unwrap();
+
+    assert_eq!(
+        history.iter().map(|t| &t.grant).collect::<Vec<_>>(),
+        vec![
+            &Grant {
+                owner: owner.clone(),
+                grantee: bob.clone(),
+                data_id: "A1".into(),
+                locked_until: 0,
+            },
+            &Grant {
+                owner: owner.clone(),
+                grantee: charlie.clone(),
+                data_id: "A1".into(),
+                locked_until: 0,
+            },
+            &Grant {
+                owner: owner.clone(),
+                grantee: bob.clone(),
+                data_id: "A2".into(),
+                locked_until: 0,
+            },
+        ]
+    );
+    assert!(history.iter().all(|t| t.deleted_by == owner));
+    assert!(history.iter().all(|t| t.reason == "deleted"));
+    assert!(history.iter().all(|t| t.deleted_at > 0));
+
+    let page = test_account
+        .call(contract.id(), "grant_history")
+        .args_json(json!({"owner": owner, "from": 1, "limit": 1}))
+        .view()
+        .await?
+        .json::<Vec<GrantTombstone>>()
+        .unwrap();
+    assert_eq!(page, history[1..2]);
+
+    let revocations = test_account
+        .call(contract.id(), "revocations_for")
+        .args_json(json!({ "grantee": bob }))
+        .view()
+        .await?
+        .json::<Vec<GrantTombstone>>()
+        .unwrap();
+    assert_eq!(
+        revocations.iter().map(|t| &t.grant.data_id).collect::<Vec<_>>(),
+        vec!["A1", "A2"]
+    );
+
+    let page = test_account
+        .call(contract.id(), "revocations_for")
+        .args_json(json!({"grantee": bob, "from": 1, "limit": 1}))
+        .view()
+        .await?
+        .json::<Vec<GrantTombstone>>()
+        .unwrap();
+    assert_eq!(page, revocations[1..2]);
+
+    Ok(())
+}
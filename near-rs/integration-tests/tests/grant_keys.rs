@@ -0,0 +1,105 @@
+use access_grants_core::{derive_grant_id, Base64VecU8, Grant, RegistryError};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{create_public_key, panicked_with, scenario_base};
+
+mod assert;
+
+mod nep413;
+use nep413::signed_args;
+
+#[tokio::test]
+async fn owner_sets_and_rotates_key_until_grant_is_deleted() -> anyhow::Result<()> {
+    let (worker, contract, test_account) = scenario_base().await?;
+
+    let owner = test_account.secret_key().public_key().to_string();
+    let grantee = create_public_key();
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({"grantee": grantee, "data_id": "A1"}))
+            .transact()
+            .await?,
+    );
+
+    let grants = test_account
+        .call(contract.id(), "find_grants")
+        .args_json(json!({ "owner": owner }))
+        .view()
+        .await?
+        .json::<Vec<Grant>>()?;
+    let grant_id = derive_grant_id(&grants[0]);
+
+    let set_grant_key = |account: &near_workspaces::Account, encrypted_key: Vec<u8>| {
+        account
+            .call(contract.id(), "set_grant_key")
+            .args_json(json!({
+                "grant_id": grant_id,
+                "encrypted_key": Base64VecU8(encrypted_key),
+            }))
+            .transact()
+    };
+    let get_grant_key = || async {
+        test_account
+            .call(contract.id(), "get_grant_key")
+            .args_json(json!({ "grant_id": grant_id }))
+            .view()
+            .await?
+            .json::<Option<Base64VecU8>>()
+    };
+
+    assert::transaction_success(set_grant_key(&test_account, vec![1; 48]).await?);
+    assert::transaction_success(set_grant_key(&test_account, vec![2; 48]).await?);
+    assert_eq!(get_grant_key().await?, Some(Base64VecU8(vec![2; 48])));
+
+    // Relayed updates are signed over the next revision, so an old one can't bring a rotated key back.
+    let signed_update = |encrypted_key: Vec<u8>, revision: u32| {
+        signed_args(
+            &test_account,
+            &contract,
+            "set_grant_key_by_signature_message",
+            json!({
+                "owner": owner,
+                "grant_id": grant_id,
+                "encrypted_key": Base64VecU8(encrypted_key),
+                "revision": revision,
+            }),
+            test_account.secret_key(),
+        )
+    };
+    let relay = |args: Value| {
+        test_account
+            .call(contract.id(), "set_grant_key_by_signature")
+            .args_json(args)
+            .transact()
+    };
+
+    let third_key = signed_update(vec![3; 48], 3).await?;
+    let fourth_key = signed_update(vec![4; 48], 4).await?;
+    assert::transaction_success(relay(third_key.clone()).await?);
+    assert::transaction_success(relay(fourth_key).await?);
+    assert::transaction_failure(
+        relay(third_key).await?,
+        &panicked_with(RegistryError::InvalidGrantKeyRevision),
+    );
+    assert_eq!(get_grant_key().await?, Some(Base64VecU8(vec![4; 48])));
+
+    let stranger = worker.dev_create_account().await?;
+    assert::transaction_failure(
+        set_grant_key(&stranger, vec![5; 48]).await?,
+        &panicked_with(RegistryError::NotOwner),
+    );
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "delete_grant")
+            .args_json(json!({"grantee": grantee, "data_id": "A1"}))
+            .transact()
+            .await?,
+    );
+    assert_eq!(get_grant_key().await?, None);
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+use access_grants_core::RegistryError;
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{
+    create_public_key, create_secret_key, extract_public_key, panicked_with, scenario_base,
+};
+
+mod assert;
+
+mod nep413;
+use nep413::signed_args;
+
+#[tokio::test]
+async fn denied_grantee_cant_get_relayed_grants() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner_sk = create_secret_key();
+    let owner = extract_public_key(&owner_sk);
+    let grantee = create_public_key();
+
+    let update_args = |listed: bool, revision: u32| {
+        json!({
+            "owner": owner,
+            "list": "deny",
+            "grantee": grantee,
+            "listed": listed,
+            "revision": revision,
+        })
+    };
+    let relay = |method: &'static str, args: Value| {
+        test_account
+            .call(contract.id(), method)
+            .args_json(args)
+            .transact()
+    };
+
+    let deny = signed_args(
+        &test_account,
+        &contract,
+        "update_grantee_list_by_signature_message",
+        update_args(true, 1),
+        &owner_sk,
+    )
+    .await?;
+    assert::transaction_success(relay("update_grantee_list_by_signature", deny).await?);
+
+    let insert = signed_args(
+        &test_account,
+        &contract,
+        "insert_grant_by_signature_message",
+        json!({"owner": owner, "grantee": grantee, "data_id": "A1"}),
+        &owner_sk,
+    )
+    .await?;
+    assert::transaction_failure(
+        relay("insert_grant_by_signature", insert).await?,
+        &panicked_with(RegistryError::GranteeDenied),
+    );
+
+    let undeny = signed_args(
+        &test_account,
+        &contract,
+        "update_grantee_list_by_signature_message",
+        update_args(false, 2),
+        &owner_sk,
+    )
+    .await?;
+    let deny_again = signed_args(
+        &test_account,
+        &contract,
+        "update_grantee_list_by_signature_message",
+        update_args(true, 3),
+        &owner_sk,
+    )
+    .await?;
+    assert::transaction_success(relay("update_grantee_list_by_signature", undeny.clone()).await?);
+    assert::transaction_success(relay("update_grantee_list_by_signature", deny_again).await?);
+
+    // The relayer can't take the grantee back off the list with the older signature.
+    assert::transaction_failure(
+        relay("update_grantee_list_by_signature", undeny).await?,
+        &panicked_with(RegistryError::InvalidListRevision),
+    );
+
+    let denied = test_account
+        .call(contract.id(), "grantee_list")
+        .args_json(json!({"owner": owner, "list": "deny"}))
+        .view()
+        .await?
+        .json::<Vec<String>>()?;
+    assert_eq!(denied, vec![grantee.clone()]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn owner_can_turn_their_allow_list_off() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner_sk = create_secret_key();
+    let owner = extract_public_key(&owner_sk);
+    let grantee = create_public_key();
+    let other_grantee = create_public_key();
+
+    let relay = |method: &'static str, args: Value| {
+        test_account
+            .call(contract.id(), method)
+            .args_json(args)
+            .transact()
+    };
+    let insert_args = |grantee: &String| {
+        signed_args(
+            &test_account,
+            &contract,
+            "insert_grant_by_signature_message",
+            json!({"owner": owner, "grantee": grantee, "data_id": "A1"}),
+            &owner_sk,
+        )
+    };
+
+    let allow = signed_args(
+        &test_account,
+        &contract,
+        "update_grantee_list_by_signature_message",
+        json!({
+            "owner": owner,
+            "list": "allow",
+            "grantee": grantee,
+            "listed": true,
+            "revision": 1,
+        }),
+        &owner_sk,
+    )
+    .await?;
+    assert::transaction_success(relay("update_grantee_list_by_signature", allow).await?);
+
+    let insert = insert_args(&other_grantee).await?;
+    assert::transaction_failure(
+        relay("insert_grant_by_signature", insert).await?,
+        &panicked_with(RegistryError::GranteeNotAllowed),
+    );
+
+    let turn_off = signed_args(
+        &test_account,
+        &contract,
+        "set_allow_list_enabled_by_signature_message",
+        json!({"owner": owner, "enabled": false, "revision": 2}),
+        &owner_sk,
+    )
+    .await?;
+    assert::transaction_success(relay("set_allow_list_enabled_by_signature", turn_off).await?);
+
+    let insert = insert_args(&other_grantee).await?;
+    assert::transaction_success(relay("insert_grant_by_signature", insert).await?);
+
+    let enabled = test_account
+        .call(contract.id(), "allow_list_enabled")
+        .args_json(json!({ "owner": owner }))
+        .view()
+        .await?
+        .json::<bool>()?;
+    assert!(!enabled);
+
+    // Who was on it stays there, for when the owner turns it back on.
+    let allowed = test_account
+        .call(contract.id(), "grantee_list")
+        .args_json(json!({"owner": owner, "list": "allow"}))
+        .view()
+        .await?
+        .json::<Vec<String>>()?;
+    assert_eq!(allowed, vec![grantee.clone()]);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use access_grants_core::{ContentHash, RegistryError};
+use serde_json::{json, Value};
+
+mod helpers;
+use helpers::{panicked_with, scenario_base};
+
+mod assert;
+
+#[tokio::test]
+async fn contract_account_verifies_and_requires_profiles() -> anyhow::Result<()> {
+    let (worker, contract, test_account) = scenario_base().await?;
+
+    let grantee_account = worker.dev_create_account().await?;
+    let grantee = grantee_account.secret_key().public_key().to_string();
+
+    let insert_grant = || {
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({"grantee": grantee, "data_id": "A1"}))
+            .transact()
+    };
+
+    // Only the contract account curates.
+    assert::transaction_failure(
+        test_account
+            .call(contract.id(), "set_require_registered_grantees")
+            .args_json(json!({"required": true}))
+            .transact()
+            .await?,
+        &panicked_with(RegistryError::NotContractAccount),
+    );
+    assert::transaction_success(
+        contract
+            .as_account()
+            .call(contract.id(), "set_require_registered_grantees")
+            .args_json(json!({"required": true}))
+            .transact()
+            .await?,
+    );
+
+    assert::transaction_failure(
+        insert_grant().await?,
+        &panicked_with(RegistryError::GranteeNotRegistered),
+    );
+
+    assert::transaction_success(
+        grantee_account
+            .call(contract.id(), "register_grantee_profile")
+            .args_json(json!({
+                "name": "Acme KYC",
+                "url": "https://acme.example",
+                "privacy_policy_hash": ContentHash([7; 32]),
+            }))
+            .transact()
+            .await?,
+    );
+    assert::transaction_success(
+        contract
+            .as_account()
+            .call(contract.id(), "set_grantee_verified")
+            .args_json(json!({"grantee": grantee, "verified": true}))
+            .transact()
+            .await?,
+    );
+
+    assert::transaction_success(insert_grant().await?);
+
+    let profile = test_account
+        .call(contract.id(), "grantee_profile")
+        .args_json(json!({ "grantee": grantee }))
+        .view()
+        .await?
+        .json::<Value>()?;
+    assert_eq!(profile["name"], "Acme KYC");
+    assert_eq!(profile["privacy_policy_hash"], json!(ContentHash([7; 32])));
+    assert_eq!(profile["logo_hash"], Value::Null);
+    assert_eq!(profile["verified"], true);
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use access_grants_core::RegistryError;
+use serde_json::json;
+
+mod helpers;
+use helpers::{create_public_key, scenario_base};
+
+mod assert;
+
+const EMPTY_ROOT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+#[tokio::test]
+async fn root_follows_grants() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let bob = create_public_key();
+
+    let root = || async {
+        anyhow::Ok(
+            test_account
+                .call(contract.id(), "grants_root")
+                .args_json(json!({}))
+                .view()
+                .await?
+                .json::<String>()?,
+        )
+    };
+
+    assert_eq!(root().await?, EMPTY_ROOT);
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({"grantee": bob, "data_id": "A1"}))
+            .transact()
+            .await?,
+    );
+    let root_with_a1 = root().await?;
+    assert_ne!(root_with_a1, EMPTY_ROOT);
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({"grantee": bob, "data_id": "A2"}))
+            .transact()
+            .await?,
+    );
+    assert_ne!(root().await?, root_with_a1);
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "delete_grant")
+            .args_json(json!({"grantee": bob, "data_id": "A2"}))
+            .transact()
+            .await?,
+    );
+    assert_eq!(root().await?, root_with_a1);
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "delete_grant")
+            .args_json(json!({"grantee": bob, "data_id": "A1"}))
+            .transact()
+            .await?,
+    );
+    assert_eq!(root().await?, EMPTY_ROOT);
+
+    assert_eq!(
+        RegistryError::find_in(&format!(
+            "{:?}",
+            test_account
+                .view(contract.id(), "grant_proof")
+                .args_json(json!({"grant_id": "not hex"}))
+                .await
+                .expect_err("grant_proof should have panicked")
+        )),
+        Some(RegistryError::InvalidGrantId)
+    );
+
+    Ok(())
+}
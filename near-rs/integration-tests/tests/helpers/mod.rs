@@ -2,6 +2,7 @@ use lazy_static::lazy_static;
 
 use std::{env, fs};
 
+use access_grants_core::RegistryError;
 use near_workspaces::{network::Sandbox, types::SecretKey, Account, Contract, Worker};
 use serde::Deserialize;
 
@@ -13,6 +14,45 @@ pub struct Grant {
     pub locked_until: u128,
 }
 
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct GrantsForKey {
+    pub grantee: Option<String>,
+    pub data_id: Option<String>,
+    pub grants: Vec<Grant>,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct GrantTombstone {
+    pub grant: Grant,
+    pub deleted_at: u64,
+    pub deleted_by: String,
+    pub reason: String,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct GrantChange {
+    pub seq: u64,
+    pub kind: String,
+    pub grant: Grant,
+    pub block_height: u64,
+    pub timestamp: u64,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct ChangesPage {
+    pub oldest_seq: u64,
+    pub latest_seq: u64,
+    pub changes: Vec<GrantChange>,
+}
+
+// What a failed transaction reports when the contract returned `error`.
+pub fn panicked_with(error: RegistryError) -> String {
+    format!(
+        r#"Action #0: ExecutionError("Smart contract panicked: {}")"#,
+        error
+    )
+}
+
 pub fn create_secret_key() -> SecretKey {
     SecretKey::from_random(near_workspaces::types::KeyType::ED25519)
 }
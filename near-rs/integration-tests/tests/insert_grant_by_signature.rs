@@ -1,11 +1,17 @@
+use std::convert::TryInto;
+
+use access_grants_core::{Ed25519Signature, Nonce, RegistryError, GRANT_MESSAGE_RECIPIENT};
 use serde_json::json;
 
 mod helpers;
-use helpers::{create_public_key, create_secret_key, extract_public_key, scenario_base, Grant};
+use helpers::{
+    create_public_key, create_secret_key, extract_public_key, panicked_with, scenario_base, Grant,
+};
 
 mod assert;
 
 mod nep413;
+use nep413::{signed_args, SignWith};
 
 #[tokio::test]
 async fn happy_path() -> anyhow::Result<()> {
@@ -17,7 +23,6 @@ async fn happy_path() -> anyhow::Result<()> {
     let grantee = create_public_key();
     let data_id: String = "DATA_ID".into();
     let locked_until = 0;
-    let nonce = nep413::generate_nonce();
 
     assert_eq!(
         test_account
@@ -30,46 +35,23 @@ async fn happy_path() -> anyhow::Result<()> {
         vec![]
     );
 
-    let recipient = test_account
-        .call(contract.id(), "grant_message_recipient")
-        .args_json(json!({}))
-        .view()
-        .await?
-        .json::<String>()
-        .unwrap();
-
-    let message = test_account
-        .call(contract.id(), "insert_grant_by_signature_message")
-        .args_json(json!({
+    let args = signed_args(
+        &test_account,
+        &contract,
+        "insert_grant_by_signature_message",
+        json!({
             "owner": owner,
             "grantee": grantee,
             "data_id": data_id,
             "locked_until": locked_until,
-        }))
-        .view()
-        .await?
-        .json::<String>()
-        .unwrap();
-
-    let signature = nep413::Payload {
-        message,
-        nonce,
-        recipient,
-        callback_url: None,
-    }
-    .sign_with(owner_sk);
-
+        }),
+        &owner_sk,
+    )
+    .await?;
     assert::transaction_success(
         test_account
             .call(contract.id(), "insert_grant_by_signature")
-            .args_json(json!({
-                "owner": owner,
-                "grantee": grantee,
-                "data_id": data_id,
-                "locked_until": locked_until,
-                "nonce": nonce,
-                "signature": signature,
-            }))
+            .args_json(args)
             .transact()
             .await?,
     );
@@ -116,7 +98,79 @@ async fn wrong_signature() -> anyhow::Result<()> {
             }))
             .transact()
             .await?,
-        r#"Action #0: ExecutionError("Smart contract panicked: Signature doesn't match")"#,
+        &panicked_with(RegistryError::InvalidSignature),
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn base64_nonce_and_signature() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner_sk = create_secret_key();
+    let owner = extract_public_key(&owner_sk);
+
+    let grantee = create_public_key();
+    let data_id: String = "DATA_ID".into();
+    let nonce = nep413::generate_nonce();
+
+    let message = test_account
+        .call(contract.id(), "insert_grant_by_signature_message")
+        .args_json(json!({"owner": owner, "grantee": grantee, "data_id": data_id}))
+        .view()
+        .await?
+        .json::<String>()
+        .unwrap();
+
+    let signature: [u8; 64] = nep413::Payload {
+        message,
+        nonce,
+        recipient: GRANT_MESSAGE_RECIPIENT.into(),
+        callback_url: None,
+    }
+    .sign_with(owner_sk)
+    .try_into()
+    .unwrap();
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant_by_signature")
+            .args_json(json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": data_id,
+                "nonce": Nonce(nonce),
+                "signature": Ed25519Signature(signature),
+            }))
+            .transact()
+            .await?,
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn wrong_signature_length() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let result = test_account
+        .call(contract.id(), "insert_grant_by_signature")
+        .args_json(json!({
+            "owner": create_public_key(),
+            "grantee": create_public_key(),
+            "data_id": "DATA_ID",
+            "nonce": nep413::generate_nonce(),
+            "signature": vec![0u8; 63],
+        }))
+        .transact()
+        .await?;
+
+    let error = result.into_result().unwrap_err().to_string();
+    assert!(
+        error.contains("invalid signature: expected 64 bytes, got 63"),
+        "{}",
+        error
     );
 
     Ok(())
@@ -0,0 +1,58 @@
+use access_grants_core::RegistryError;
+use serde_json::json;
+
+mod helpers;
+use helpers::{panicked_with, scenario_base};
+
+mod assert;
+
+const FIVE_YEARS: u64 = 5 * 365 * 24 * 60 * 60 * 1_000_000_000;
+const ONE_HOUR: u64 = 60 * 60 * 1_000_000_000;
+
+#[tokio::test]
+async fn inserts_follow_the_grantees_lock_policy() -> anyhow::Result<()> {
+    let (worker, contract, test_account) = scenario_base().await?;
+
+    let grantee_account = worker.dev_create_account().await?;
+    let grantee = grantee_account.secret_key().public_key().to_string();
+
+    assert::transaction_success(
+        grantee_account
+            .call(contract.id(), "set_lock_policies")
+            .args_json(json!({
+                "policies": [{"data_id_prefix": "kyc/", "min_lock_duration": FIVE_YEARS}],
+            }))
+            .transact()
+            .await?,
+    );
+
+    let insert_grant = |data_id: &str, locked_until: u64| {
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({
+                "grantee": grantee,
+                "data_id": data_id,
+                "locked_until": locked_until,
+            }))
+            .transact()
+    };
+
+    let min_locked_until = test_account
+        .call(contract.id(), "min_locked_until")
+        .args_json(json!({"grantee": grantee, "data_id": "kyc/passport"}))
+        .view()
+        .await?
+        .json::<u64>()?;
+    assert!(min_locked_until > FIVE_YEARS);
+
+    assert::transaction_failure(
+        insert_grant("kyc/passport", min_locked_until - ONE_HOUR).await?,
+        &panicked_with(RegistryError::LockTooShort),
+    );
+    assert::transaction_success(insert_grant("kyc/passport", min_locked_until + ONE_HOUR).await?);
+
+    // Data outside the policy's prefix can be granted without a lock.
+    assert::transaction_success(insert_grant("email", 0).await?);
+
+    Ok(())
+}
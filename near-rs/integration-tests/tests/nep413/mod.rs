@@ -1,35 +1,19 @@
-use borsh::BorshSerialize;
 use rand::random;
-use ring::digest::{digest, SHA256};
-use serde::Serialize;
 
-use near_workspaces::types::SecretKey;
+use near_workspaces::{types::SecretKey, Account, Contract};
+use serde_json::{json, Value};
 use std::str::FromStr;
-use std::convert::TryInto;
-
-#[derive(BorshSerialize, Serialize)]
-pub struct Payload {
-    pub message: String,
-    pub nonce: [u8; 32],
-    pub recipient: String,
-    #[serde(rename = "callbackUrl")]
-    pub callback_url: Option<String>,
-}
-
-const NEP413_TAG: u32 = 2147484061; // 2**31 + 413
 
-impl Payload {
-    pub fn to_hashed(&self) -> [u8; 32] {
-        let mut writer = vec![];
+use access_grants_core::nep413_hashed_payload;
+pub use access_grants_core::Nep413Payload as Payload;
 
-        borsh::to_writer(&mut writer, &NEP413_TAG).expect("Can't borsh encode NEP413_TAG");
-        borsh::to_writer(&mut writer, self).expect("Can't borsh encode payload");
-
-        digest(&SHA256, writer.as_slice()).as_ref().try_into().unwrap()
-    }
+pub trait SignWith {
+    fn sign_with(&self, secret_key: SecretKey) -> Vec<u8>;
+}
 
-    pub fn sign_with(&self, secret_key: SecretKey) -> Vec<u8> {
-        let bytes = raw_sign(secret_key, &self.to_hashed());
+impl SignWith for Payload {
+    fn sign_with(&self, secret_key: SecretKey) -> Vec<u8> {
+        let bytes = raw_sign(secret_key, &nep413_hashed_payload(self));
 
         IntoIterator::into_iter(bytes).collect()
     }
@@ -43,6 +27,54 @@ pub fn generate_nonce() -> [u8; 32] {
     result
 }
 
+// Signs `message` for the contract with `signer_sk`, returning the nonce and the signature.
+pub async fn sign_message(
+    account: &Account,
+    contract: &Contract,
+    message: String,
+    signer_sk: &SecretKey,
+) -> anyhow::Result<([u8; 32], Vec<u8>)> {
+    let recipient = account
+        .call(contract.id(), "grant_message_recipient")
+        .args_json(json!({}))
+        .view()
+        .await?
+        .json::<String>()?;
+
+    let nonce = generate_nonce();
+    let signature = Payload {
+        message,
+        nonce,
+        recipient,
+        callback_url: None,
+    }
+    .sign_with(signer_sk.clone());
+
+    Ok((nonce, signature))
+}
+
+// Adds `nonce` and `signature` for `message_method`'s message to `args`, signed with `signer_sk`.
+pub async fn signed_args(
+    account: &Account,
+    contract: &Contract,
+    message_method: &str,
+    mut args: Value,
+    signer_sk: &SecretKey,
+) -> anyhow::Result<Value> {
+    let message = account
+        .call(contract.id(), message_method)
+        .args_json(&args)
+        .view()
+        .await?
+        .json::<String>()?;
+    let (nonce, signature) = sign_message(account, contract, message, signer_sk).await?;
+
+    args["nonce"] = json!(nonce);
+    args["signature"] = json!(signature);
+
+    Ok(args)
+}
+
 fn raw_sign(secret_key: SecretKey, data: &[u8]) -> [u8; 64] {
     let crypto_secret_key =
         near_crypto::SecretKey::from_str(secret_key.to_string().as_str()).unwrap();
@@ -0,0 +1,70 @@
+use serde_json::json;
+
+mod helpers;
+use helpers::{create_public_key, create_secret_key, extract_public_key, scenario_base};
+
+mod assert;
+
+mod nep413;
+use nep413::sign_message;
+
+#[tokio::test]
+async fn signed_prefix_grant_covers_data_ids_under_it() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner_sk = create_secret_key();
+    let owner = extract_public_key(&owner_sk);
+    let grantee = create_public_key();
+
+    for operation in ["insert", "delete"] {
+        let message = test_account
+            .call(
+                contract.id(),
+                &format!("{}_prefix_grant_by_signature_message", operation),
+            )
+            .args_json(json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id_prefix": "creds/",
+            }))
+            .view()
+            .await?
+            .json::<String>()
+            .unwrap();
+        assert!(message.contains("\ndataIdPrefix: creds/\n"));
+
+        let (nonce, signature) = sign_message(&test_account, &contract, message, &owner_sk).await?;
+
+        assert::transaction_success(
+            test_account
+                .call(
+                    contract.id(),
+                    &format!("{}_prefix_grant_by_signature", operation),
+                )
+                .args_json(json!({
+                    "owner": owner,
+                    "grantee": grantee,
+                    "data_id_prefix": "creds/",
+                    "nonce": nonce,
+                    "signature": signature,
+                }))
+                .transact()
+                .await?,
+        );
+
+        let has_access = |data_id: &'static str| {
+            test_account
+                .call(contract.id(), "has_access")
+                .args_json(json!({"grantee": grantee, "data_id": data_id}))
+                .view()
+        };
+
+        assert_eq!(
+            has_access("creds/passport").await?.json::<bool>().unwrap(),
+            operation == "insert"
+        );
+        assert!(!has_access("wallets/1").await?.json::<bool>().unwrap());
+    }
+
+    Ok(())
+}
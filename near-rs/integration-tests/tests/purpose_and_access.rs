@@ -0,0 +1,87 @@
+use access_grants_core::RegistryError;
+use serde_json::json;
+
+mod helpers;
+use helpers::{
+    create_public_key, create_secret_key, extract_public_key, panicked_with, scenario_base,
+};
+
+mod assert;
+
+mod nep413;
+use nep413::sign_message;
+
+#[tokio::test]
+async fn signed_purpose_and_access_are_recorded_and_filterable() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner_sk = create_secret_key();
+    let owner = extract_public_key(&owner_sk);
+    let grantee = create_public_key();
+    let access = json!({"mode": "read_only", "fields": ["name", "dob"]});
+
+    let message = test_account
+        .call(contract.id(), "insert_grant_by_signature_message")
+        .args_json(json!({
+            "owner": owner,
+            "grantee": grantee,
+            "data_id": "A1",
+            "purpose": "kyc",
+            "access": access,
+        }))
+        .view()
+        .await?
+        .json::<String>()?;
+    assert!(message.ends_with("purpose: kyc\naccess: read_only\nfields: name,dob"));
+
+    let (nonce, signature) = sign_message(&test_account, &contract, message, &owner_sk).await?;
+
+    let insert = |purpose: &str| {
+        test_account
+            .call(contract.id(), "insert_grant_by_signature")
+            .args_json(json!({
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": "A1",
+                "purpose": purpose,
+                "access": access,
+                "nonce": nonce,
+                "signature": signature,
+            }))
+            .transact()
+    };
+
+    // The purpose is part of what the owner signed.
+    assert::transaction_failure(
+        insert("marketing").await?,
+        &panicked_with(RegistryError::InvalidSignature),
+    );
+    assert::transaction_success(insert("kyc").await?);
+
+    let find_grants = |purpose: &str, access_mode: &str| {
+        test_account
+            .call(contract.id(), "find_grants")
+            .args_json(json!({
+                "owner": owner,
+                "purpose": purpose,
+                "access_mode": access_mode,
+            }))
+            .view()
+    };
+
+    let grants = find_grants("kyc", "read_only")
+        .await?
+        .json::<Vec<serde_json::Value>>()?;
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0]["purpose"], "kyc");
+    assert_eq!(grants[0]["access"], access);
+
+    assert_eq!(
+        find_grants("kyc", "read_write")
+            .await?
+            .json::<Vec<serde_json::Value>>()?,
+        vec![]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use access_grants_core::RegistryError;
+use serde_json::json;
+
+mod helpers;
+use helpers::{create_secret_key, extract_public_key, panicked_with, scenario_base, Grant};
+
+mod assert;
+
+mod events;
+use events::extract_event;
+
+mod nep413;
+use nep413::signed_args;
+
+#[tokio::test]
+async fn grantee_renounces_timelocked_grant_by_signature() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner = test_account.secret_key().public_key().to_string();
+    let grantee_sk = create_secret_key();
+    let grantee = extract_public_key(&grantee_sk);
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({
+                "grantee": grantee,
+                "data_id": "A1",
+                "locked_until": u64::MAX,
+            }))
+            .transact()
+            .await?,
+    );
+
+    let args = signed_args(
+        &test_account,
+        &contract,
+        "renounce_grant_by_signature_message",
+        json!({
+            "owner": owner,
+            "grantee": grantee,
+            "data_id": "A1",
+            "revision": 1,
+        }),
+        &grantee_sk,
+    )
+    .await?;
+    let renounce = || {
+        test_account
+            .call(contract.id(), "renounce_grant_by_signature")
+            .args_json(&args)
+            .transact()
+    };
+
+    let result = renounce().await?;
+    assert!(result.is_success());
+    assert_eq!(
+        extract_event(result.logs()[0]),
+        json!({
+            "standard": "FractalRegistry",
+            "version": "0",
+            "event": "grant_renounced",
+            "data": {
+                "owner": owner,
+                "grantee": grantee,
+                "data_id": "A1",
+                "scope": "exact",
+                "locked_until": u64::MAX,
+            },
+        })
+    );
+
+    assert_eq!(
+        test_account
+            .call(contract.id(), "find_grants")
+            .args_json(json!({ "owner": owner }))
+            .view()
+            .await?
+            .json::<Vec<Grant>>()
+            .unwrap(),
+        vec![]
+    );
+
+    // Given again, the grant gets its old id back, but the renounce signed for the old one is spent.
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({"grantee": grantee, "data_id": "A1"}))
+            .transact()
+            .await?,
+    );
+    assert::transaction_failure(
+        renounce().await?,
+        &panicked_with(RegistryError::InvalidRenounceRevision),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use access_grants_core::RegistryError;
+use serde_json::json;
+
+mod helpers;
+use helpers::{create_public_key, panicked_with, scenario_base, Grant};
+
+mod assert;
+
+mod nep413;
+use nep413::signed_args;
+
+#[tokio::test]
+async fn signed_revoke_all_resumes_and_keeps_timelocked_grants() -> anyhow::Result<()> {
+    let (worker, contract, test_account) = scenario_base().await?;
+
+    let (owner_id, owner_sk) = worker.dev_generate().await;
+    let owner_account = worker
+        .create_tla(owner_id.clone(), owner_sk.clone())
+        .await?
+        .unwrap();
+    let owner = owner_sk.public_key().to_string();
+
+    let bob = create_public_key();
+    let charlie = create_public_key();
+
+    for (grantee, data_id, locked_until) in [
+        (&bob, "A1", 0),
+        (&bob, "A2", 0),
+        (&charlie, "A1", 0),
+        (&charlie, "A2", u64::MAX),
+    ] {
+        assert::transaction_success(
+            owner_account
+                .call(contract.id(), "insert_grant")
+                .args_json(json!({
+                    "grantee": grantee,
+                    "data_id": data_id,
+                    "locked_until": locked_until,
+                }))
+                .transact()
+                .await?,
+        );
+    }
+
+    let mut args = signed_args(
+        &test_account,
+        &contract,
+        "revoke_all_by_signature_message",
+        json!({ "owner": owner, "revision": 1 }),
+        &owner_sk,
+    )
+    .await?;
+    args["limit"] = json!(2);
+    let revoke = || {
+        test_account
+            .call(contract.id(), "revoke_all_by_signature")
+            .args_json(&args)
+            .transact()
+    };
+
+    let first = revoke().await?.into_result()?.json::<serde_json::Value>()?;
+    assert_eq!(first["revoked"], 2);
+    assert!(first["next"].is_string());
+
+    let second = revoke().await?.into_result()?.json::<serde_json::Value>()?;
+    assert_eq!(second["revoked"], 1);
+    assert_eq!(second["next"], serde_json::Value::Null);
+    assert_eq!(second["timelocked"][0]["data_id"], "A2");
+
+    // Once done, the signature is spent: it can't wipe grants inserted later.
+    assert::transaction_success(
+        owner_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({"grantee": bob, "data_id": "A3"}))
+            .transact()
+            .await?,
+    );
+    assert::transaction_failure(
+        revoke().await?,
+        &panicked_with(RegistryError::InvalidRevokeAllRevision),
+    );
+
+    assert_eq!(
+        test_account
+            .call(contract.id(), "find_grants")
+            .args_json(json!({ "owner": owner }))
+            .view()
+            .await?
+            .json::<Vec<Grant>>()?,
+        vec![
+            Grant {
+                owner: owner.clone(),
+                grantee: charlie,
+                data_id: "A2".into(),
+                locked_until: u64::MAX.into(),
+            },
+            Grant {
+                owner,
+                grantee: bob,
+                data_id: "A3".into(),
+                locked_until: 0.into(),
+            },
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use access_grants_core::Grant;
+use serde_json::json;
+
+mod helpers;
+use helpers::{scenario_base, GrantsForKey};
+
+mod assert;
+
+// Some time in 2096, well after any sandbox block.
+const LATER: u64 = 4_000_000_000_000_000_000;
+
+#[tokio::test]
+async fn grants_only_give_access_from_valid_from_on() -> anyhow::Result<()> {
+    let (worker, contract, test_account) = scenario_base().await?;
+
+    let grantee = worker
+        .dev_create_account()
+        .await?
+        .secret_key()
+        .public_key()
+        .to_string();
+    let owner = test_account.secret_key().public_key().to_string();
+
+    let insert_grant = |data_id: &str, valid_from: u64| {
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({
+                "grantee": grantee,
+                "data_id": data_id,
+                "valid_from": valid_from,
+            }))
+            .transact()
+    };
+    let has_access = |data_id: &str| {
+        test_account
+            .call(contract.id(), "has_access")
+            .args_json(json!({"grantee": grantee, "data_id": data_id}))
+            .view()
+    };
+
+    assert::transaction_success(insert_grant("audit/2026", LATER).await?);
+    assert::transaction_success(insert_grant("kyc", 1).await?);
+
+    assert!(!has_access("audit/2026").await?.json::<bool>()?);
+    assert!(has_access("kyc").await?.json::<bool>()?);
+
+    let grants_for = test_account
+        .call(contract.id(), "grants_for")
+        .args_json(json!({"grantee": grantee, "data_id": "audit/2026"}))
+        .view()
+        .await?
+        .json::<Vec<Grant>>()?;
+    assert_eq!(grants_for, vec![]);
+
+    // The owner still sees the grant before it starts.
+    let grants = test_account
+        .call(contract.id(), "find_grants")
+        .args_json(json!({"owner": owner, "data_id": "audit/2026"}))
+        .view()
+        .await?
+        .json::<Vec<Grant>>()?;
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].valid_from, LATER);
+
+    let find_grants_multi = |active_only: bool| {
+        test_account
+            .call(contract.id(), "find_grants_multi")
+            .args_json(json!({
+                "grantees": [grantee],
+                "data_ids": ["audit/2026", "kyc"],
+                "active_only": active_only,
+            }))
+            .view()
+    };
+    let grant_counts = |results: Vec<GrantsForKey>| {
+        results
+            .iter()
+            .map(|result| result.grants.len())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        grant_counts(find_grants_multi(false).await?.json()?),
+        vec![1, 1]
+    );
+    assert_eq!(
+        grant_counts(find_grants_multi(true).await?.json()?),
+        vec![0, 1]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use access_grants_core::{consent_hash, derive_grant_id, Grant};
+use serde_json::json;
+
+mod helpers;
+use helpers::{create_public_key, scenario_base};
+
+mod assert;
+
+#[tokio::test]
+async fn consent_hash_is_stored_and_verified() -> anyhow::Result<()> {
+    let (_, contract, test_account) = scenario_base().await?;
+
+    let owner = test_account.secret_key().public_key().to_string();
+    let terms = "You may check my age using my passport, and nothing else.";
+
+    assert::transaction_success(
+        test_account
+            .call(contract.id(), "insert_grant")
+            .args_json(json!({
+                "grantee": create_public_key(),
+                "data_id": "A1",
+                "consent_hash": consent_hash(terms),
+            }))
+            .transact()
+            .await?,
+    );
+
+    let grants = test_account
+        .call(contract.id(), "find_grants")
+        .args_json(json!({ "owner": owner }))
+        .view()
+        .await?
+        .json::<Vec<Grant>>()?;
+    assert_eq!(grants[0].consent_hash, Some(consent_hash(terms)));
+
+    let verify_consent = |text: &str| {
+        test_account
+            .call(contract.id(), "verify_consent")
+            .args_json(json!({"grant_id": derive_grant_id(&grants[0]), "text": text}))
+            .view()
+    };
+
+    assert!(verify_consent(terms).await?.json::<bool>()?);
+    assert!(!verify_consent("You may do anything.")
+        .await?
+        .json::<bool>()?);
+
+    Ok(())
+}